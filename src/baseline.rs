@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use crate::checker::{Action, Diagnostic};
+
+/// Sentence text as a baseline compares it: lowercase, with runs of
+/// whitespace made single spaces, and curly quotes made straight, so that
+/// reflowing or retyping a sentence doesn't make its findings new.
+pub fn normalise(text: &str) -> String {
+    let straightened: String = text.chars()
+        .map(|c| match c {
+            '‘' | '’' => '\'',
+            '“' | '”' => '"',
+            c => c,
+        })
+        .collect();
+    straightened.to_lowercase().split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+//64 bit FNV-1a, which unlike std's hasher is the same from one Rust
+//release to the next
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// A finding's fingerprint. It's made from the rule, and the normalised
+/// words and sentence, and not where the finding is, so it survives
+/// paragraphs being added, removed and renumbered.
+pub fn fingerprint(diagnostic: &Diagnostic) -> String {
+    let key = format!("{}\u{0}{}\u{0}{}", diagnostic.action.name(), normalise(&diagnostic.text), normalise(&diagnostic.sentence));
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+/// A finding recorded in a baseline.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BaselineEntry {
+    pub rule: Action,
+    /// The normalised sentence, for people reading the baseline; it's the
+    /// fingerprint that's matched.
+    pub sentence: String,
+    pub fingerprint: String,
+}
+
+/// Findings that are already known about, and shouldn't be reported again.
+#[derive(Debug)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Baseline {
+    pub findings: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn new(diagnostics: &[Diagnostic]) -> Baseline {
+        let findings = diagnostics.iter()
+            .map(|diagnostic| BaselineEntry {
+                rule: diagnostic.action,
+                sentence: normalise(&diagnostic.sentence),
+                fingerprint: fingerprint(diagnostic),
+            })
+            .collect();
+        Baseline { findings }
+    }
+
+    /// The findings that aren't in the baseline. A sentence recorded with
+    /// one finding of a rule and now having two has one new one.
+    pub fn new_findings(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let mut known: HashMap<&str, usize> = HashMap::new();
+        for entry in &self.findings {
+            *known.entry(&entry.fingerprint).or_default() += 1;
+        }
+        diagnostics.into_iter()
+            .filter(|diagnostic| match known.get_mut(fingerprint(diagnostic).as_str()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::check_markdown;
+    use crate::config::Config;
+
+    fn weak_immediacy(text: &str) -> Vec<Diagnostic> {
+        check_markdown(text, Action::WeakImmediacy, Config::default())
+    }
+
+    #[test]
+    fn fingerprints_ignore_whitespace_case_and_quote_style() {
+        let straight = weak_immediacy("The girl's friend was there just then.\n");
+        let curly = weak_immediacy("The  girl\u{2019}s friend was\nthere Just then.\n");
+        assert_eq!(straight.len(), 1);
+        assert_eq!(curly.len(), 1);
+        assert_eq!(fingerprint(&straight[0]), fingerprint(&curly[0]));
+        assert!(Baseline::new(&straight).new_findings(curly).is_empty());
+    }
+
+    #[test]
+    fn fingerprints_differ_by_rule_and_sentence() {
+        let one = weak_immediacy("She was there just then.\n");
+        let other = weak_immediacy("He was there just then.\n");
+        assert_ne!(fingerprint(&one[0]), fingerprint(&other[0]));
+        let mut adverb = one[0].clone();
+        adverb.action = Action::Adverb;
+        assert_ne!(fingerprint(&one[0]), fingerprint(&adverb));
+    }
+
+    #[test]
+    fn duplicate_findings_are_counted() {
+        let twice = weak_immediacy("She was there just then, and just then left.\n");
+        assert_eq!(twice.len(), 2);
+        assert_eq!(fingerprint(&twice[0]), fingerprint(&twice[1]));
+
+        let mut once = Baseline::new(&twice);
+        once.findings.pop();
+        assert_eq!(once.new_findings(twice.clone()).len(), 1);
+        assert!(Baseline::new(&twice).new_findings(twice).is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use std::{cmp::Ordering, fs, path::{Path, PathBuf}};
+
+use rayon::prelude::*;
+
+use crate::config::Config;
+use crate::document::{Document, DocumentElement};
+use crate::error::TeastainError;
+use crate::format::{parse, Format};
+
+/// The name of the manifest that lists a book's files.
+pub const MANIFEST_NAME: &str = "book.toml";
+
+/// The files that make up a book, in reading order, as listed in a
+/// book.toml, e.g.
+///
+/// ```toml
+/// title = "Sugar Houses"
+/// files = ["prologue.odt", "chapter 1.odt", "chapter 2.odt"]
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub title: String,
+    /// Relative to the manifest.
+    pub files: Vec<PathBuf>,
+}
+
+impl Manifest {
+    /// The manifest's files, for a manifest in `dir`.
+    pub fn paths(&self, dir: &Path) -> Vec<PathBuf> {
+        self.files.iter().map(|file| dir.join(file)).collect()
+    }
+}
+
+//split a name into runs of digits and runs of everything else
+fn name_chunks(name: &str) -> Vec<&str> {
+    let mut out = vec![];
+    let mut start = 0;
+    for (i, c) in name.char_indices().skip(1) {
+        let previous = name[..i].chars().next_back().unwrap_or(c);
+        if previous.is_ascii_digit() != c.is_ascii_digit() {
+            out.push(&name[start..i]);
+            start = i;
+        }
+    }
+    out.push(&name[start..]);
+    out
+}
+
+//order names so that 'chapter 10' comes after 'chapter 9'
+fn compare_names(a: &str, b: &str) -> Ordering {
+    for (a, b) in name_chunks(a).into_iter().zip(name_chunks(b)) {
+        let ordering = if a.starts_with(|c: char| c.is_ascii_digit()) && b.starts_with(|c: char| c.is_ascii_digit()) {
+            let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a.len().cmp(&b.len()).then(a.cmp(b))
+        } else {
+            a.cmp(b)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// The chapter files in a directory: every file we can read, ordered by
+/// name.
+pub fn chapter_files(dir: &Path) -> Result<Vec<PathBuf>, TeastainError> {
+    let mut out = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if Format::of(&path).is_some() && path.is_file() {
+            out.push(path);
+        }
+    }
+    out.sort_by(|a, b| compare_names(&a.to_string_lossy(), &b.to_string_lossy()));
+    Ok(out)
+}
+
+/// Join documents into one book, each starting a new chapter.
+pub fn stitch(documents: Vec<Document>) -> Document {
+    let mut out = Document { elements: vec![], number_of_chapters: 0, warnings: vec![] };
+    for mut document in documents {
+        if !out.elements.is_empty() {
+            out.elements.push(DocumentElement::ChapterEnd);
+        }
+        out.elements.append(&mut document.elements);
+        out.number_of_chapters += document.number_of_chapters;
+        out.warnings.append(&mut document.warnings);
+    }
+    out.number_of_chapters = out.number_of_chapters.max(1);
+    out
+}
+
+/// Read a book's files, in parallel, and join them together. Errors and
+/// warnings say which of the files they came from.
+pub fn parse_files(paths: &[PathBuf], config: &Config) -> Result<Document, TeastainError> {
+    let documents = paths.par_iter()
+        .map(|path| {
+            let in_file = |error| TeastainError::File { path: path.clone(), error: Box::new(error) };
+            let mut document = parse(path, config).map_err(in_file)?;
+            document.warnings = document.warnings.into_iter().map(in_file).collect();
+            Ok(document)
+        })
+        .collect::<Result<Vec<Document>, TeastainError>>()?;
+    Ok(stitch(documents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{Action, Checker, Diagnostic};
+    use crate::markdown::parse_str;
+
+    //check two files as one book, a chapter to each
+    fn check_book(first: &str, second: &str, action: Action) -> Vec<Diagnostic> {
+        let config = Config::default();
+        let book = stitch(vec![parse_str(first, "one.md", &config), parse_str(second, "two.md", &config)]);
+        Checker::with_rules(config, vec![action]).check(&book)
+    }
+
+    #[test]
+    fn words_overused_across_the_book_are_reported_in_each_chapter() {
+        let found = check_book("He nodded. She nodded. They nodded.\n", "We nodded. You nodded. I nodded.\n", Action::OverusedWord);
+        let found: Vec<(i32, Option<usize>)> = found.iter().map(|d| (d.location.chapter_number, d.count)).collect();
+        assert_eq!(found, vec![(1, Some(3)), (2, Some(3))]);
+    }
+
+    #[test]
+    fn names_are_found_across_the_book() {
+        //'Shadows' only opens sentences in the second file, but the first
+        //shows it isn't a name
+        let found = check_book("The shadows were long.\n", "Shadows fell. Shadows grew.\n", Action::Echo);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].location.chapter_number, 2);
+    }
+
+    #[test]
+    fn echoes_stop_at_the_end_of_a_file() {
+        assert!(check_book("The crimson sky.\n", "Her crimson scarf.\n", Action::Echo).is_empty());
+    }
+}
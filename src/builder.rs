@@ -0,0 +1,369 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::config::Config;
+use crate::document::{Document, DocumentElement, Paragraph, Sentence, Span, Speech, Word};
+use crate::quotes::{QuoteToken, QuoteTokenizer};
+use crate::suppress::Suppression;
+
+pub fn paragraph_is_section_break(line: &str) -> bool {
+    let trimmed_line = line.trim();
+    trimmed_line == "* * *" || trimmed_line == "#"
+}
+
+//narration that runs straight into or out of speech, with no sentence break
+//in between, is the speech's attribution
+fn narration_element(sentence: Sentence, is_speech_tag: bool) -> DocumentElement {
+    if is_speech_tag {
+        DocumentElement::SpeechTag(sentence)
+    } else {
+        DocumentElement::Sentence(sentence)
+    }
+}
+
+//speech that was opened at `open_speech_at` was never closed, so mark it
+fn report_unclosed_quote(doc_out: &mut Vec<DocumentElement>, open_speech_at: &mut Option<usize>) {
+    if let Some(i) = open_speech_at.take() {
+        if let DocumentElement::DirectSpeech(speech) = &doc_out[i] {
+            if let Some(first_sentence) = speech.sentences.first() {
+                let first_sentence = first_sentence.clone();
+                doc_out.insert(i, DocumentElement::UnclosedQuote(first_sentence));
+            }
+        }
+    }
+}
+
+//a sentence ends at its terminator, not the space after it
+fn trim_sentence(mut words: Vec<Word>, paragraph_index: usize) -> Sentence {
+    while words.last().is_some_and(|w| w.text.trim().is_empty()) {
+        words.pop();
+    }
+    Sentence::new(words, paragraph_index)
+}
+
+fn ends_with_abbreviation(sentence: &str, config: &Config) -> bool {
+    match sentence.trim_end().strip_suffix('.') {
+        Some(before_stop) => {
+            let word = before_stop.rsplit(|c: char| c.is_whitespace() || "‘“\"'«‹(".contains(c)).next().unwrap_or("");
+            let lowercase_word = word.to_lowercase();
+            config.abbreviations.contains(&lowercase_word) || config.extra_abbreviations.contains(&lowercase_word)
+        }
+        None => false,
+    }
+}
+
+//the byte offsets where sentences start, after the first. We use the Unicode
+//sentence boundaries, which get '?!', '...' and decimals right, but they
+//can't know that 'Mr.' doesn't end a sentence. A project's own abbreviations
+//count as well as the built-in ones
+fn sentence_boundaries(paragraph: &str, config: &Config) -> Vec<usize> {
+    let mut out = vec![];
+    for (start, sentence) in paragraph.split_sentence_bound_indices() {
+        let end = start + sentence.len();
+        if end < paragraph.len() && !ends_with_abbreviation(sentence, config) {
+            out.push(end);
+        }
+    }
+    out
+}
+
+fn parse_paragraph(
+    paragraph: &Paragraph,
+    tokenizer: &mut QuoteTokenizer,
+    config: &Config
+) -> Vec<DocumentElement>{
+    let mut out = vec![DocumentElement::ParagraphStart(paragraph.clone())];
+    let boundaries = sentence_boundaries(&paragraph.text, config);
+    let mut next_boundary = boundaries.iter().peekable();
+
+    let mut sentence: Vec<Word> = vec![];
+    let mut speech: Vec<Sentence> = vec![];
+    let mut direct_speech = false;
+    let mut after_speech = false;
+    let mut char_offset = 0;
+    for (offset, text, token) in tokenizer.tokenize(&paragraph.text) {
+        let char_length = text.chars().count();
+        let span = Span {
+            start: offset,
+            end: offset + text.len(),
+            char_start: char_offset,
+            char_end: char_offset + char_length,
+        };
+        char_offset += char_length;
+
+        let is_whitespace = token == QuoteToken::Word && text.trim().is_empty();
+        if !is_whitespace && next_boundary.next_if(|b| offset >= **b).is_some() {
+            while next_boundary.next_if(|b| offset >= **b).is_some() {}
+            if direct_speech {
+                if !sentence.is_empty() {
+                    speech.push(trim_sentence(sentence, paragraph.index));
+                    sentence = vec![];
+                }
+            } else {
+                if !sentence.is_empty() {
+                    out.push(narration_element(trim_sentence(sentence, paragraph.index), after_speech));
+                    sentence = vec![];
+                }
+                after_speech = false;
+            }
+        }
+
+        match token {
+            QuoteToken::SpeechOpen => {
+                if !sentence.is_empty() {
+                    out.push(DocumentElement::SpeechTag(trim_sentence(sentence, paragraph.index)));
+                    sentence = vec![];
+                }
+                direct_speech = true;
+            }
+            QuoteToken::SpeechClose => {
+                if !sentence.is_empty() {
+                    speech.push(trim_sentence(sentence, paragraph.index));
+                }
+                out.push(DocumentElement::DirectSpeech(Speech { sentences: speech }));
+                speech = vec![];
+                direct_speech = false;
+                after_speech = true;
+                sentence = vec![];
+            }
+            QuoteToken::Word => {
+                //skip leading space
+                if !(is_whitespace && sentence.is_empty()) {
+                    sentence.push(Word { text: text.to_string(), span });
+                }
+            }
+        }
+    }
+    if direct_speech {
+        //speech carrying on into the next paragraph
+        if !sentence.is_empty() {
+            speech.push(trim_sentence(sentence, paragraph.index));
+        }
+        out.push(DocumentElement::DirectSpeech(Speech { sentences: speech }));
+    } else if !sentence.is_empty() {
+        out.push(narration_element(trim_sentence(sentence, paragraph.index), after_speech));
+    }
+    out.push(DocumentElement::ParagraphEnd);
+    out
+}
+
+/// Builds a document from a file's paragraphs, one at a time. Readers for
+/// each format decide what's a title, a break or a paragraph; this splits
+/// paragraphs into speech and sentences, carrying speech from one paragraph
+/// to the next.
+pub struct DocumentBuilder<'a> {
+    config: &'a Config,
+    elements: Vec<DocumentElement>,
+    number_of_chapters: i32,
+    tokenizer: QuoteTokenizer,
+    //the speech that's still open at the end of a paragraph, if any
+    open_speech_at: Option<usize>,
+    //suppressions for the next paragraph
+    suppressions: Vec<Suppression>,
+}
+
+impl<'a> DocumentBuilder<'a> {
+    pub fn new(config: &'a Config) -> DocumentBuilder<'a> {
+        DocumentBuilder {
+            config,
+            elements: vec![],
+            number_of_chapters: 1,
+            tokenizer: QuoteTokenizer::new(config.quote_convention),
+            open_speech_at: None,
+            suppressions: vec![],
+        }
+    }
+
+    //every paragraph, whatever it turns out to be, can close off speech
+    //left open by the one before
+    fn start_paragraph(&mut self, text: &str) {
+        if self.tokenizer.start_paragraph(text) {
+            report_unclosed_quote(&mut self.elements, &mut self.open_speech_at);
+        }
+    }
+
+    pub fn chapter_end(&mut self) {
+        //a file that opens with a page break would otherwise start with an
+        //empty chapter
+        if self.elements.is_empty() {
+            return;
+        }
+        self.elements.push(DocumentElement::ChapterEnd);
+        self.number_of_chapters += 1;
+    }
+
+    pub fn chapter_title(&mut self, text: String) {
+        self.start_paragraph(&text);
+        self.elements.push(DocumentElement::ChapterTitle(text));
+    }
+
+    pub fn chapter_name(&mut self, text: String) {
+        self.start_paragraph(&text);
+        self.elements.push(DocumentElement::ChapterName(text));
+    }
+
+    pub fn section_end(&mut self, text: &str) {
+        self.start_paragraph(text);
+        self.elements.push(DocumentElement::SectionEnd);
+    }
+
+    /// Suppress rules in the next paragraph, and the section or chapter
+    /// it's in. Suppressions in titles and section breaks go to the
+    /// paragraph after them.
+    pub fn suppress(&mut self, suppression: Suppression) {
+        self.suppressions.push(suppression);
+    }
+
+    pub fn paragraph(&mut self, mut paragraph: Paragraph) {
+        paragraph.suppressions.append(&mut self.suppressions);
+        self.start_paragraph(&paragraph.text);
+        let continuing_speech = self.tokenizer.in_speech();
+        let mut sentences = parse_paragraph(&paragraph, &mut self.tokenizer, self.config);
+        let speeches = sentences.iter().filter(|e| matches!(e, DocumentElement::DirectSpeech(_))).count();
+        let last_speech = sentences.iter().rposition(|e| matches!(e, DocumentElement::DirectSpeech(_)));
+        if !self.tokenizer.in_speech() {
+            self.open_speech_at = None;
+        } else if !(continuing_speech && speeches == 1) {
+            self.open_speech_at = last_speech.map(|i| self.elements.len() + i);
+        }
+        self.elements.append(&mut sentences);
+    }
+
+    pub fn finish(mut self) -> Document {
+        if self.tokenizer.finish() {
+            report_unclosed_quote(&mut self.elements, &mut self.open_speech_at);
+        }
+        Document { elements: self.elements, number_of_chapters: self.number_of_chapters, warnings: vec![] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_abbreviations() {
+        let config = Config::default();
+        assert_eq!(sentence_boundaries("Mr. Smith left. He was late.", &config), vec![16]);
+    }
+
+    #[test]
+    fn extra_abbreviations_add_to_the_built_in_ones() {
+        let config = Config { extra_abbreviations: vec![String::from("ch")], ..Config::default() };
+        assert_eq!(sentence_boundaries("Mr. Smith read ch. 3 today. He liked it.", &config), vec![28]);
+    }
+
+    fn parse(text: &str) -> Vec<DocumentElement> {
+        crate::markdown::parse_str(text, "test.md", &Config::default()).elements
+    }
+
+    //what kind of element each is, and the text of its first sentence
+    fn summary(elements: &[DocumentElement]) -> Vec<(&'static str, String)> {
+        let text = |sentence: &Sentence| sentence.words.iter().map(|w| w.text.as_str()).collect::<String>();
+        elements.iter()
+            .filter_map(|element| match element {
+                DocumentElement::DirectSpeech(speech) => Some(("speech", text(&speech.sentences[0]))),
+                DocumentElement::UnclosedQuote(sentence) => Some(("unclosed", text(sentence))),
+                DocumentElement::Sentence(sentence) => Some(("sentence", text(sentence))),
+                DocumentElement::SpeechTag(sentence) => Some(("tag", text(sentence))),
+                DocumentElement::ParagraphStart(_) => Some(("paragraph", String::from(""))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn pairs(expected: &[(&'static str, &str)]) -> Vec<(&'static str, String)> {
+        expected.iter().map(|(kind, text)| (*kind, text.to_string())).collect()
+    }
+
+    #[test]
+    fn speech_continued_in_the_next_paragraph() {
+        let elements = parse("‘The first part.\n\n‘The second part,’ she said.\n");
+        assert_eq!(summary(&elements), pairs(&[
+            ("paragraph", ""),
+            ("speech", "The first part."),
+            ("paragraph", ""),
+            ("speech", "The second part,"),
+            ("tag", "she said."),
+        ]));
+    }
+
+    #[test]
+    fn speech_never_closed_before_the_end() {
+        let elements = parse("She waited.\n\nHe said, ‘Hello there.\n");
+        assert_eq!(summary(&elements), pairs(&[
+            ("paragraph", ""),
+            ("sentence", "She waited."),
+            ("paragraph", ""),
+            ("tag", "He said,"),
+            ("unclosed", "Hello there."),
+            ("speech", "Hello there."),
+        ]));
+    }
+
+    #[test]
+    fn unclosed_quote_followed_by_narration() {
+        let elements = parse("‘Hello there.\n\nShe left.\n");
+        assert_eq!(summary(&elements), pairs(&[
+            ("paragraph", ""),
+            ("unclosed", "Hello there."),
+            ("speech", "Hello there."),
+            ("paragraph", ""),
+            ("sentence", "She left."),
+        ]));
+    }
+
+    #[test]
+    fn unclosed_quote_is_found_at_its_sentence() {
+        let diagnostics = crate::checker::check_markdown(
+            "Intro.\n\nShe waited. ‘Hello there.\n\nShe left.\n",
+            crate::checker::Action::UnclosedQuote,
+            Config::default(),
+        );
+        assert_eq!(diagnostics.len(), 1);
+        let location = &diagnostics[0].location;
+        assert_eq!((location.paragraph_number, location.sentence_number), (2, 2));
+        assert_eq!(diagnostics[0].text, "Hello there.");
+    }
+
+    #[test]
+    fn speech_split_into_sentences() {
+        let elements = parse("‘Run. Hide now! Where, though?’ she said. ‘Mr. Smith knows.’\n");
+        let speeches: Vec<Vec<String>> = elements.iter()
+            .filter_map(|element| match element {
+                DocumentElement::DirectSpeech(speech) => Some(speech.sentences.iter()
+                    .map(|s| s.words.iter().map(|w| w.text.as_str()).collect())
+                    .collect()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(speeches, vec![
+            vec![String::from("Run."), String::from("Hide now!"), String::from("Where, though?")],
+            vec![String::from("Mr. Smith knows.")],
+        ]);
+    }
+
+    #[test]
+    fn spans_point_into_the_paragraph() {
+        let text = "Zoë ran. ‘Café, now,’ she said.";
+        let source = format!("# Title\n\n{}\n", text);
+        let elements = parse(&source);
+        let mut sentences = vec![];
+        for element in &elements {
+            match element {
+                DocumentElement::ParagraphStart(paragraph) => assert_eq!(&source[paragraph.offset..][..text.len()], text),
+                DocumentElement::Sentence(sentence) | DocumentElement::SpeechTag(sentence) => sentences.push(sentence),
+                DocumentElement::DirectSpeech(speech) => sentences.extend(&speech.sentences),
+                _ => (),
+            }
+        }
+        let chars: Vec<char> = text.chars().collect();
+        for sentence in &sentences {
+            for word in &sentence.words {
+                assert_eq!(&text[word.span.start..word.span.end], word.text);
+                assert_eq!(chars[word.span.char_start..word.span.char_end].iter().collect::<String>(), word.text);
+            }
+        }
+        let spanned: Vec<&str> = sentences.iter().map(|s| &text[s.span.start..s.span.end]).collect();
+        assert_eq!(spanned, vec!["Zoë ran.", "Café, now,", "she said."]);
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt::Write;
+
+use crate::document::Document;
+use crate::stats::{sentence_lengths, Stats};
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 320.0;
+//room for the title at the top, the labels at the bottom and the scale on
+//the left
+const TOP: f64 = 40.0;
+const BOTTOM: f64 = 60.0;
+const LEFT: f64 = 56.0;
+const RIGHT: f64 = 16.0;
+const GRID_LINES: usize = 4;
+//how many sentences the moving average of sentence length is over
+const SMOOTHING: usize = 10;
+
+const BAR_COLOUR: &str = "#4a7fb5";
+const LINE_COLOUR: &str = "#c0c8d0";
+const SMOOTHED_COLOUR: &str = "#b5494a";
+
+/// A chart, as an SVG element with no XML prolog, so that it can be saved
+/// as a file or put straight into an HTML page.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Chart {
+    /// A name for the chart that can go in a file name, e.g.
+    /// "chapter-words".
+    pub name: &'static str,
+    pub svg: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+//a round number at least as big as `max`, for the top of the scale
+fn scale_top(max: f64) -> f64 {
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0].iter()
+        .map(|step| step * magnitude)
+        .find(|top| *top >= max)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn plot_height() -> f64 {
+    HEIGHT - TOP - BOTTOM
+}
+
+fn plot_width() -> f64 {
+    WIDTH - LEFT - RIGHT
+}
+
+fn y_for(value: f64, top: f64) -> f64 {
+    TOP + plot_height() * (1.0 - value / top)
+}
+
+//the outline, title and scale every chart has
+fn start_chart(title: &str, top: f64) -> String {
+    let mut svg = String::new();
+    let _ = write!(svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" \
+        font-family=\"sans-serif\" font-size=\"11\" role=\"img\" aria-label=\"{t}\">\n\
+        <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n\
+        <text x=\"{x}\" y=\"24\" text-anchor=\"middle\" font-size=\"15\" font-weight=\"bold\">{t}</text>\n",
+        w = WIDTH, h = HEIGHT, x = WIDTH / 2.0, t = escape(title));
+    for i in 0..=GRID_LINES {
+        let value = top * i as f64 / GRID_LINES as f64;
+        let y = y_for(value, top);
+        let _ = writeln!(svg,
+            "<line x1=\"{}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"#e0e0e0\"/>\
+            <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            LEFT, WIDTH - RIGHT, LEFT - 6.0, y + 4.0, format_value(value));
+    }
+    svg
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{}", value) } else { format!("{:.1}", value) }
+}
+
+/// A bar chart of labelled values.
+pub fn bar_chart(name: &'static str, title: &str, bars: &[(String, f64)]) -> Chart {
+    let top = scale_top(bars.iter().map(|(_, v)| *v).fold(0.0, f64::max));
+    let mut svg = start_chart(title, top);
+    let slot = plot_width() / bars.len().max(1) as f64;
+    //a label for every bar, unless they'd run into each other
+    let label_every = (bars.len() as f64 * 28.0 / plot_width()).ceil().max(1.0) as usize;
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let x = LEFT + slot * i as f64 + slot * 0.15;
+        let y = y_for(*value, top);
+        let _ = writeln!(svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {}</title></rect>",
+            x, y, slot * 0.7, TOP + plot_height() - y, BAR_COLOUR, escape(label), format_value((value * 10.0).round() / 10.0));
+        if i % label_every == 0 {
+            let label_x = LEFT + slot * (i as f64 + 0.5);
+            let label_y = TOP + plot_height() + 14.0;
+            let _ = writeln!(svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" transform=\"rotate(-35 {:.1} {:.1})\">{}</text>",
+                label_x, label_y, label_x, label_y, escape(&short_label(label)));
+        }
+    }
+    svg.push_str("</svg>\n");
+    Chart { name, svg }
+}
+
+fn short_label(label: &str) -> String {
+    if label.chars().count() > 18 {
+        label.chars().take(17).collect::<String>() + "…"
+    } else {
+        label.to_string()
+    }
+}
+
+fn polyline(values: &[f64], top: f64, colour: &str, width: f64) -> String {
+    let step = plot_width() / (values.len().max(2) - 1) as f64;
+    let points: Vec<String> = values.iter().enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", LEFT + step * i as f64, y_for(*v, top)))
+        .collect();
+    format!("<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n", points.join(" "), colour, width)
+}
+
+/// A line chart of values in order, with their moving average over
+/// `smoothing` values on top, and `caption` under it.
+pub fn line_chart(name: &'static str, title: &str, caption: &str, values: &[f64], smoothing: usize) -> Chart {
+    let top = scale_top(values.iter().copied().fold(0.0, f64::max));
+    let mut svg = start_chart(title, top);
+    if !values.is_empty() {
+        let smoothed: Vec<f64> = (0..values.len())
+            .map(|i| {
+                let window = &values[(i + 1).saturating_sub(smoothing.max(1))..=i];
+                window.iter().sum::<f64>() / window.len() as f64
+            })
+            .collect();
+        svg += &polyline(values, top, LINE_COLOUR, 1.0);
+        svg += &polyline(&smoothed, top, SMOOTHED_COLOUR, 2.0);
+    }
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+        LEFT + plot_width() / 2.0, HEIGHT - 16.0, escape(caption));
+    svg.push_str("</svg>\n");
+    Chart { name, svg }
+}
+
+fn chapter_label(number: i32, title: &str) -> String {
+    if title.is_empty() { format!("Chapter {}", number) } else { title.to_string() }
+}
+
+/// The pacing charts for a document: sentence length through the book,
+/// how much of each section is dialogue, and the findings and words in
+/// each chapter.
+pub fn pacing_charts(document: &Document, stats: &Stats) -> Vec<Chart> {
+    let lengths: Vec<f64> = sentence_lengths(document).into_iter().map(|l| l as f64).collect();
+
+    let mut dialogue = vec![];
+    let mut findings = vec![];
+    let mut words = vec![];
+    for chapter in &stats.chapter_stats {
+        for section in &chapter.sections {
+            dialogue.push((format!("{}.{}", chapter.number, section.number), section.counts.speech_ratio * 100.0));
+        }
+        let label = chapter_label(chapter.number, &chapter.title);
+        findings.push((label.clone(), chapter.counts.findings_per_thousand_words));
+        words.push((label, chapter.counts.words as f64));
+    }
+
+    let caption = format!("Each sentence in order. The red line is the average over {} sentences", SMOOTHING);
+    vec![
+        line_chart("sentence-lengths", "Sentence length in words", &caption, &lengths, SMOOTHING),
+        bar_chart("dialogue", "Dialogue, % of words per section", &dialogue),
+        bar_chart("findings", "Findings per 1,000 words per chapter", &findings),
+        bar_chart("chapter-words", "Words per chapter", &words),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_finite(svg: &str) -> bool {
+        !svg.contains("NaN") && !svg.contains("inf")
+    }
+
+    #[test]
+    fn empty_charts() {
+        let bars = bar_chart("bars", "Bars", &[]);
+        assert!(bars.svg.starts_with("<svg") && bars.svg.ends_with("</svg>\n"));
+        assert!(!bars.svg.contains("<rect x="));
+        assert!(is_finite(&bars.svg));
+
+        let line = line_chart("line", "Line", "Caption", &[], SMOOTHING);
+        assert!(line.svg.ends_with("</svg>\n"));
+        assert!(!line.svg.contains("<polyline"));
+        assert!(is_finite(&line.svg));
+    }
+
+    #[test]
+    fn single_value_charts() {
+        let bars = bar_chart("bars", "Bars", &[(String::from("Chapter 1"), 50.0)]);
+        assert_eq!(bars.svg.matches("<rect x=").count(), 1);
+        assert!(bars.svg.contains("<title>Chapter 1: 50</title>"));
+        //the bar reaches the top of the scale
+        assert!(bars.svg.contains(&format!("y=\"{:.1}\"", TOP)));
+        assert!(is_finite(&bars.svg));
+
+        let line = line_chart("line", "Line", "Caption", &[12.0], SMOOTHING);
+        assert_eq!(line.svg.matches("<polyline").count(), 2);
+        assert!(line.svg.contains(&format!("points=\"{:.1},{:.1}\"", LEFT, y_for(12.0, 20.0))));
+        assert!(is_finite(&line.svg));
+    }
+
+    #[test]
+    fn zero_values() {
+        let bars = bar_chart("bars", "Bars", &[(String::from("Chapter 1"), 0.0)]);
+        assert!(is_finite(&bars.svg));
+        assert_eq!(scale_top(0.0), 1.0);
+        assert_eq!(scale_top(12.0), 20.0);
+        assert_eq!(scale_top(250.0), 250.0);
+    }
+}
@@ -0,0 +1,572 @@
+use std::{collections::{HashMap, HashSet}, fmt};
+
+use rayon::prelude::*;
+
+use crate::config::Config;
+use crate::database::{init_database, init_dictionary, ActionTrigger, DictionaryElem, PartOfSpeech};
+use crate::document::{Document, DocumentElement, Sentence, Span, Word};
+use crate::echo::{names_in, EchoTracker};
+use crate::fixes::suggestion;
+use crate::frequency::{overused_in, overused_in_book, overused_word};
+use crate::matcher::Matcher;
+use crate::openings::{OpeningRun, OpeningTracker};
+use crate::passive::find_passive_voice;
+use crate::suppress::{Suppression, SuppressionScope};
+use crate::tags::{analyse_speech_tag, TagFinding};
+
+/// The rules teastain checks.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Action{
+    Filtering,
+    Beginning,
+    WeakImmediacy,
+    PotentialAdverb,
+    Adverb,
+    Contraction,
+    SubjectiveAdjective,
+    PassiveVoice,
+    Echo,
+    RepeatedOpening,
+    SaidBookism,
+    TagAdverb,
+    UnclosedQuote,
+    OverusedWord,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::Filtering,
+        Action::Beginning,
+        Action::WeakImmediacy,
+        Action::PotentialAdverb,
+        Action::Adverb,
+        Action::Contraction,
+        Action::SubjectiveAdjective,
+        Action::PassiveVoice,
+        Action::Echo,
+        Action::RepeatedOpening,
+        Action::SaidBookism,
+        Action::TagAdverb,
+        Action::UnclosedQuote,
+        Action::OverusedWord,
+    ];
+
+    /// The name rules go by on the command line and in config files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Filtering => "filtering",
+            Action::Beginning => "beginning",
+            Action::WeakImmediacy => "weak-immediacy",
+            Action::PotentialAdverb => "potential-adverb",
+            Action::Adverb => "adverb",
+            Action::Contraction => "contraction",
+            Action::SubjectiveAdjective => "subjective-adjective",
+            Action::PassiveVoice => "passive-voice",
+            Action::Echo => "echo",
+            Action::RepeatedOpening => "repeated-opening",
+            Action::SaidBookism => "said-bookism",
+            Action::TagAdverb => "tag-adverb",
+            Action::UnclosedQuote => "unclosed-quote",
+            Action::OverusedWord => "overused-word",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// What the rule looks for, and why it matters.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Action::Filtering =>
+                "Perception verbs like 'saw', 'heard' and 'watched' put a viewpoint character \
+                between the reader and the story. Show what was seen rather than the seeing.",
+            Action::Beginning =>
+                "'Started to' and 'began to' usually add nothing: 'she began to run' is \
+                weaker than 'she ran'.",
+            Action::WeakImmediacy =>
+                "Words like 'suddenly' and 'immediately' announce speed instead of creating \
+                it. The sentence is usually stronger without them.",
+            Action::PotentialAdverb =>
+                "A word ending in -ly that isn't in the dictionary, so may be an adverb. \
+                A stronger verb often does the same job.",
+            Action::Adverb =>
+                "An adverb. A stronger verb often does the same job.",
+            Action::Contraction =>
+                "Words that people would contract when speaking, like 'I am' or 'do not'. \
+                Also checked in speech, where uncontracted words sound stiff.",
+            Action::SubjectiveAdjective =>
+                "Adjectives like 'beautiful' or 'good' tell the reader what to think rather \
+                than showing them something to think it about.",
+            Action::PassiveVoice =>
+                "A form of 'to be' followed by a past participle, hiding who did the action. \
+                Participles usually used as adjectives, like 'tired', only count with a 'by' \
+                agent. Not checked in speech.",
+            Action::Echo =>
+                "A word used again within a few sentences of itself, in the same scene. Names, \
+                found across the whole book, and words shorter than the configured length, \
+                are ignored.",
+            Action::RepeatedOpening =>
+                "Several sentences in a row opening with the same words, e.g. 'She'. \
+                Vary how sentences start. A run ends at speech and at a scene break.",
+            Action::SaidBookism =>
+                "A fancy substitute for 'said' in a dialogue tag. 'Said' and 'asked' are \
+                invisible to the reader; 'expostulated' is not.",
+            Action::TagAdverb =>
+                "An adverb in a dialogue tag, like 'she said angrily'. Let the speech \
+                show how it's said.",
+            Action::UnclosedQuote =>
+                "Speech whose opening quotation mark is never closed, and which isn't \
+                carried on by the next paragraph.",
+            Action::OverusedWord =>
+                "A word used more often in a chapter, or in the whole book, than the configured \
+                rate per 10,000 words, that's either on the configured list, like 'nodded', or \
+                used far more than in English in general. Reported once a chapter, where it's \
+                first used, with how many times the chapter uses it. Not checked in speech.",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Filtering => "possible filtering",
+            Action::Beginning => "beginning",
+            Action::WeakImmediacy => "weak immediacy",
+            Action::PotentialAdverb => "potential adverb",
+            Action::Adverb => "adverb",
+            Action::Contraction => "missed contraction",
+            Action::SubjectiveAdjective => "subjective adjective",
+            Action::PassiveVoice => "passive voice",
+            Action::Echo => "echo",
+            Action::RepeatedOpening => "repeated sentence opening",
+            Action::SaidBookism => "said-bookism",
+            Action::TagAdverb => "adverb in dialogue tag",
+            Action::UnclosedQuote => "unclosed quote",
+            Action::OverusedWord => "overused word",
+        }
+    }
+}
+
+pub(crate) fn skip_action_in_direct_speech(action: Action) -> bool {
+    action != Action::Contraction
+}
+
+/// Where in the document a finding was made.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    pub chapter_title: String,
+    pub chapter_number: i32,
+    pub section_number: i32,
+    pub paragraph_number: i32,
+    pub sentence_number: i32,
+    pub number_of_chapters: i32,
+    /// The file the finding is in.
+    pub file: String,
+    /// Which paragraph of the source file, counting from 0.
+    pub paragraph_index: usize,
+    /// Where the paragraph is in the source file.
+    pub source_path: String,
+    /// The byte offset the paragraph starts at in the source file, for
+    /// formats where that means something.
+    pub offset: usize,
+    /// The part of the paragraph's text that the finding covers.
+    pub span: Span,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.chapter_title.is_empty() {
+            write!(f, "{}, ", self.chapter_title)?;
+        } else if self.number_of_chapters > 1 {
+            write!(f, "Chapter {}, ", self.chapter_number)?;
+        }
+        write!(f, "Section {}, Paragraph {}", self.section_number, self.paragraph_number)?;
+        if self.sentence_number > 0 {
+            write!(f, ", Sentence {}", self.sentence_number)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single finding.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub action: Action,
+    pub location: Location,
+    /// The words that triggered the rule.
+    pub text: String,
+    /// The sentence they were found in, or the sentences for rules that
+    /// look at more than one.
+    pub sentence: String,
+    /// What to replace the words with, for rules that know.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub suggestion: Option<String>,
+    /// How many times the words are used, for rules that report them once
+    /// for a whole chapter, like overused words.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub count: Option<usize>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {} ({:?}", self.location, self.action.description(), self.text)?;
+        if let Some(count) = self.count {
+            write!(f, ", used {} times in the chapter", count)?;
+        }
+        write!(f, ")\n{:?}\n  at {}, paragraph {} ({}), characters {}-{}",
+            self.sentence, self.location.file, self.location.paragraph_index, self.location.source_path,
+            self.location.span.char_start, self.location.span.char_end)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  suggestion: {:?}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+fn words_span(words: &[Word]) -> Span {
+    match (words.first(), words.last()) {
+        (Some(first), Some(last)) => first.span.to(&last.span),
+        _ => Span::default(),
+    }
+}
+
+fn sentence_string(words: &[Word]) -> String {
+    words.iter().map(|w| w.text.as_str()).collect()
+}
+
+fn process_action(
+    action: Action,
+    sentence: &[Word],
+    history: &[Word],
+    location: &Location,
+    diagnostics: &mut Vec<Diagnostic>
+) {
+    //words we've made up to describe a finding don't have a span, so fall
+    //back on the one the finding came with
+    let span = words_span(history);
+    let mut location = location.clone();
+    if span.char_end > 0 {
+        location.span = span;
+    }
+    diagnostics.push(Diagnostic {
+        action,
+        location,
+        text: sentence_string(history),
+        sentence: sentence_string(sentence),
+        suggestion: None,
+        count: None,
+    });
+}
+
+fn process_sentence(
+    sentence: &[Word],
+    database: &HashMap<String, Vec<ActionTrigger>>,
+    dictionary: &HashMap<String, DictionaryElem>,
+    location: &Location,
+    direct_speech: bool,
+    diagnostics: &mut Vec<Diagnostic>
+) {
+    //phrases never run across a sentence boundary
+    let mut matcher = Matcher::new();
+    for word in sentence {
+        for m in matcher.push_word(word, database, direct_speech) {
+            process_action(m.action, sentence, &m.history, location, diagnostics);
+        }
+
+        if !direct_speech && word.text.ends_with("ly") {
+            let o_dictionary_elem = dictionary.get(&word.text);
+            match o_dictionary_elem {
+                Some(dictionary_elem) => {
+                    if dictionary_elem.part_of_speech == PartOfSpeech::Adverb {
+                        process_action(Action::Adverb, sentence, std::slice::from_ref(word), location, diagnostics);
+                    }
+                }
+                None => process_action(Action::PotentialAdverb, sentence, std::slice::from_ref(word), location, diagnostics)
+            }
+
+        }
+    }
+
+    if !(direct_speech && skip_action_in_direct_speech(Action::PassiveVoice)) {
+        for history in find_passive_voice(sentence, dictionary) {
+            process_action(Action::PassiveVoice, sentence, &history, location, diagnostics);
+        }
+    }
+}
+
+fn process_echo(
+    sentence: &[Word],
+    echo_tracker: &mut EchoTracker,
+    location: &Location,
+    diagnostics: &mut Vec<Diagnostic>
+) {
+    for word in echo_tracker.push_sentence(sentence) {
+        process_action(Action::Echo, sentence, &[word], location, diagnostics);
+    }
+}
+
+//each overused word is reported once a chapter, where it's first used,
+//with how often it's used
+fn process_overused(
+    sentence: &[Word],
+    overused: &HashMap<String, usize>,
+    reported: &mut HashSet<String>,
+    location: &Location,
+    diagnostics: &mut Vec<Diagnostic>
+) {
+    for word in sentence {
+        let Some(counted) = overused_word(word, overused) else {
+            continue;
+        };
+        if !reported.insert(counted.clone()) {
+            continue;
+        }
+        process_action(Action::OverusedWord, sentence, std::slice::from_ref(word), location, diagnostics);
+        if let Some(diagnostic) = diagnostics.last_mut() {
+            diagnostic.count = overused.get(&counted).copied();
+        }
+    }
+}
+
+fn process_speech_tag(
+    tag: &[Word],
+    config: &Config,
+    location: &Location,
+    diagnostics: &mut Vec<Diagnostic>
+) {
+    for finding in analyse_speech_tag(tag, config) {
+        match finding {
+            TagFinding::Bookism(verb) => process_action(Action::SaidBookism, tag, &[verb], location, diagnostics),
+            TagFinding::Adverb(adverb) => process_action(Action::TagAdverb, tag, &[adverb], location, diagnostics),
+        }
+    }
+}
+
+fn process_opening_run(run: Option<OpeningRun>, diagnostics: &mut Vec<Diagnostic>) {
+    if let Some(run) = run {
+        let opening = Word::synthetic(&run.opening.join(" "));
+        process_action(Action::RepeatedOpening, &run.text, &[opening], &run.location, diagnostics);
+    }
+}
+
+//a suppression, and the section and paragraph it was attached to
+struct PlacedSuppression<'a> {
+    suppression: &'a Suppression,
+    section_number: i32,
+    paragraph_number: i32,
+}
+
+fn is_suppressed(diagnostic: &Diagnostic, suppressions: &[PlacedSuppression]) -> bool {
+    suppressions.iter().any(|placed| {
+        let location = &diagnostic.location;
+        let in_scope = match placed.suppression.scope {
+            SuppressionScope::Paragraph =>
+                placed.section_number == location.section_number && placed.paragraph_number == location.paragraph_number,
+            SuppressionScope::Section => placed.section_number == location.section_number,
+            SuppressionScope::Chapter => true,
+        };
+        in_scope && placed.suppression.covers(diagnostic.action)
+    })
+}
+
+fn start_sentence(location: &mut Location, sentence: &Sentence) {
+    location.sentence_number += 1;
+    location.paragraph_index = sentence.paragraph_index;
+    location.span = sentence.span;
+}
+
+//what's found by reading the whole book before its chapters are checked
+struct Book {
+    names: HashSet<String>,
+    overused: HashSet<String>,
+}
+
+/// Runs a set of rules over documents.
+pub struct Checker {
+    config: Config,
+    rules: Vec<Action>,
+    database: HashMap<String, Vec<ActionTrigger>>,
+    dictionary: HashMap<String, DictionaryElem>,
+}
+
+impl Checker {
+    /// A checker that runs every rule.
+    pub fn new(config: Config) -> Checker {
+        Checker::with_rules(config, Action::ALL.to_vec())
+    }
+
+    /// A checker that only reports findings for the given rules.
+    pub fn with_rules(config: Config, rules: Vec<Action>) -> Checker {
+        Checker {
+            config,
+            rules,
+            database: init_database(),
+            dictionary: init_dictionary(),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn rules(&self) -> &[Action] {
+        &self.rules
+    }
+
+    /// Check a document, returning the findings in document order. The
+    /// whole book is read first for the names in it and the words it
+    /// overuses; after that, echoes and repeated openings start afresh at a
+    /// chapter break, so chapters are checked in parallel. Findings the
+    /// author has suppressed aren't reported.
+    pub fn check(&self, document: &Document) -> Vec<Diagnostic> {
+        let book = Book {
+            names: names_in(&document.elements),
+            overused: overused_in_book(&document.elements, &self.config),
+        };
+        let chapters: Vec<&[DocumentElement]> = document.elements
+            .split_inclusive(|e| matches!(e, DocumentElement::ChapterEnd))
+            .collect();
+        let mut diagnostics = chapters.into_par_iter()
+            .enumerate()
+            .map(|(i, elements)| self.check_chapter(elements, &book, i as i32 + 1, document.number_of_chapters))
+            .collect::<Vec<Vec<Diagnostic>>>()
+            .concat();
+
+        diagnostics.retain(|d| self.rules.contains(&d.action));
+        for diagnostic in &mut diagnostics {
+            diagnostic.suggestion = suggestion(diagnostic.action, &diagnostic.text, &self.config);
+        }
+        diagnostics
+    }
+
+    //the elements of one chapter, up to and including its ChapterEnd
+    fn check_chapter(&self, elements: &[DocumentElement], book: &Book, chapter_number: i32, number_of_chapters: i32) -> Vec<Diagnostic> {
+        let config = &self.config;
+        let database = &self.database;
+        let dictionary = &self.dictionary;
+        let mut diagnostics = vec![];
+
+        let mut location = Location{
+            chapter_title: String::from(""),
+            chapter_number,
+            section_number: 1,
+            paragraph_number: 1,
+            sentence_number: 0,
+            number_of_chapters,
+            file: String::from(""),
+            paragraph_index: 0,
+            source_path: String::from(""),
+            offset: 0,
+            span: Span::default(),
+        };
+        let mut suppressions = vec![];
+        //how often words are used is only known once the whole chapter's
+        //been read
+        let overused = overused_in(elements, config, &book.overused);
+        let mut overused_reported = HashSet::new();
+        let mut echo_tracker = EchoTracker::new(config, &book.names);
+        let mut opening_tracker = OpeningTracker::new(config.opening_run_length);
+
+        for element in elements {
+            match element {
+                DocumentElement::ChapterEnd | DocumentElement::SectionEnd | DocumentElement::ParagraphEnd =>
+                    location.sentence_number = 0,
+                DocumentElement::Sentence(sentence) | DocumentElement::SpeechTag(sentence) =>
+                    start_sentence(&mut location, sentence),
+                _ => ()
+            }
+            match element {
+                DocumentElement::ChapterEnd | DocumentElement::SectionEnd | DocumentElement::DirectSpeech(_) =>
+                    process_opening_run(opening_tracker.finish(), &mut diagnostics),
+                _ => ()
+            }
+            match element {
+                DocumentElement::ChapterEnd => {
+                    location.chapter_number += 1;
+                    location.chapter_title = String::from("");
+                    location.section_number = 1;
+                    location.paragraph_number = 1;
+                    echo_tracker.reset();
+                }
+                DocumentElement::SectionEnd => {
+                    location.section_number += 1;
+                    location.paragraph_number = 1;
+                    echo_tracker.reset();
+                }
+                DocumentElement::ParagraphEnd => {
+                    location.paragraph_number += 1;
+                }
+                DocumentElement::ParagraphStart(paragraph) => {
+                    location.file = paragraph.file.clone();
+                    location.paragraph_index = paragraph.index;
+                    location.source_path = paragraph.source_path.clone();
+                    location.offset = paragraph.offset;
+                    suppressions.extend(paragraph.suppressions.iter().map(|suppression| PlacedSuppression {
+                        suppression,
+                        section_number: location.section_number,
+                        paragraph_number: location.paragraph_number,
+                    }));
+                }
+                DocumentElement::ChapterName(_) => (),
+                DocumentElement::ChapterTitle(this_chapter_title) => {
+                    location.chapter_title = this_chapter_title.clone();
+                },
+                DocumentElement::DirectSpeech(speech) => {
+                    for sentence in &speech.sentences {
+                        start_sentence(&mut location, sentence);
+                        process_sentence(&sentence.words, database, dictionary, &location, true, &mut diagnostics);
+                    }
+                }
+                DocumentElement::Sentence(sentence) => {
+                    process_sentence(&sentence.words, database, dictionary, &location, false, &mut diagnostics);
+                    process_echo(&sentence.words, &mut echo_tracker, &location, &mut diagnostics);
+                    process_overused(&sentence.words, &overused, &mut overused_reported, &location, &mut diagnostics);
+                    process_opening_run(opening_tracker.push_sentence(&sentence.words, &location), &mut diagnostics);
+                }
+                DocumentElement::UnclosedQuote(speech) => {
+                    //the speech comes next, so its first sentence is the
+                    //one with the quote in
+                    let mut location = location.clone();
+                    start_sentence(&mut location, speech);
+                    process_action(Action::UnclosedQuote, &speech.words, &speech.words, &location, &mut diagnostics);
+                }
+                DocumentElement::SpeechTag(tag) => {
+                    process_sentence(&tag.words, database, dictionary, &location, false, &mut diagnostics);
+                    process_echo(&tag.words, &mut echo_tracker, &location, &mut diagnostics);
+                    process_overused(&tag.words, &overused, &mut overused_reported, &location, &mut diagnostics);
+                    process_speech_tag(&tag.words, config, &location, &mut diagnostics);
+                }
+            }
+        }
+        process_opening_run(opening_tracker.finish(), &mut diagnostics);
+
+        //a suppression covers its whole paragraph, section or chapter, even
+        //the findings made before we got to it
+        diagnostics.retain(|d| !is_suppressed(d, &suppressions));
+        diagnostics
+    }
+}
+
+/// Check Markdown text with one rule, as the rules' tests do.
+#[cfg(test)]
+pub(crate) fn check_markdown(text: &str, action: Action, config: Config) -> Vec<Diagnostic> {
+    let document = crate::markdown::parse_str(text, "test.md", &config);
+    Checker::with_rules(config, vec![action]).check(&document)
+}
+
+/// The text of each finding one rule makes in Markdown text, with the
+/// default settings.
+#[cfg(test)]
+pub(crate) fn findings(text: &str, action: Action) -> Vec<String> {
+    check_markdown(text, action, Config::default()).into_iter().map(|d| d.text).collect()
+}
@@ -0,0 +1,94 @@
+use crate::quotes::QuoteConvention;
+
+/// The reading ages a manuscript is aimed at, e.g. 12 to 14.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadingAgeBand {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ReadingAgeBand {
+    pub fn contains(&self, age: f64) -> bool {
+        age >= self.min as f64 && age <= self.max as f64
+    }
+}
+
+/// Settings that tune how the rules behave.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Config {
+    /// How many sentences back the echo rule looks for a repeated word.
+    pub echo_window: usize,
+    /// Words shorter than this are never reported as echoes.
+    pub echo_min_word_length: usize,
+    /// Names the echo rule should ignore, on top of the ones it spots itself.
+    pub character_names: Vec<String>,
+    /// How many sentences in a row must open the same way to be reported.
+    /// Anything less than 2 counts as 2.
+    pub opening_run_length: usize,
+    /// Plain speech verbs that dialogue tags should use.
+    pub speech_verbs: Vec<String>,
+    /// Speech verbs that are reported when used in a dialogue tag.
+    pub said_bookisms: Vec<String>,
+    /// Which quotation marks the manuscript uses for speech.
+    pub quote_convention: QuoteConvention,
+    /// Abbreviations whose full stop doesn't end a sentence, lowercase and
+    /// without the final stop, e.g. 'mr' or 'u.s'. Setting this replaces
+    /// the built-in list.
+    pub abbreviations: Vec<String>,
+    /// Abbreviations to add to the built-in ones, e.g. 'ch'.
+    pub extra_abbreviations: Vec<String>,
+    /// The reading ages chapters should be within. Stats flags those that
+    /// aren't.
+    pub reading_age: Option<ReadingAgeBand>,
+    /// Words the overused word rule watches for, on top of the ones used
+    /// far more than in English in general.
+    pub overused_words: Vec<String>,
+    /// How many times per 10,000 words of a chapter a word has to be used
+    /// to be reported as overused.
+    pub overused_word_rate: f64,
+    /// How many times more often than in English in general a word has to
+    /// be used to be reported as overused, if it isn't one of the
+    /// overused words.
+    pub overused_word_ratio: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            echo_window: 5,
+            echo_min_word_length: 4,
+            character_names: vec![],
+            opening_run_length: 3,
+            speech_verbs: ["ask", "asked", "asks", "say", "said", "says"]
+                .iter().map(|w| w.to_string()).collect(),
+            said_bookisms: [
+                "admonished", "barked", "bellowed", "blurted", "boomed", "chortled", "chuckled",
+                "cried", "declared", "demanded", "ejaculated", "exclaimed", "gasped", "giggled",
+                "grinned", "groaned", "growled", "grunted", "hissed", "howled", "interjected",
+                "laughed", "moaned", "mumbled", "murmured", "muttered", "opined", "pleaded",
+                "purred", "queried", "retorted", "roared", "scoffed", "screamed", "shouted",
+                "shrieked", "sighed", "smiled", "smirked", "snapped", "snarled", "sneered",
+                "sniffed", "snorted", "spat", "stammered", "stated", "stuttered", "wailed",
+                "whimpered", "whined", "whispered", "yelled",
+            ].iter().map(|w| w.to_string()).collect(),
+            quote_convention: QuoteConvention::UkSingle,
+            abbreviations: [
+                "capt", "col", "dr", "e.g", "gen", "i.e", "jr", "lt", "messrs", "mr", "mrs", "ms",
+                "mt", "prof", "rev", "sgt", "sr", "st", "u.k", "u.s", "vs",
+            ].iter().map(|w| w.to_string()).collect(),
+            extra_abbreviations: vec![],
+            reading_age: None,
+            overused_words: [
+                "chuckled", "frowned", "gazed", "glanced", "grinned", "nodded", "shrugged", "sighed",
+                "smirked", "suddenly",
+            ].iter().map(|w| w.to_string()).collect(),
+            overused_word_rate: 10.0,
+            overused_word_ratio: 10.0,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use crate::checker::Action;
+
+pub struct ActionTrigger{
+    pub action: Action,
+    pub trigger: Vec<String>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum PartOfSpeech{
+    Adjective,
+    Adverb,
+    Noun,
+    PastParticiple,
+    /// A word that looks like a past participle but isn't one, e.g. 'need'.
+    NotParticiple,
+}
+
+#[derive(Debug)]
+pub struct DictionaryElem{
+    pub part_of_speech: PartOfSpeech
+}
+
+pub fn init_dictionary() -> HashMap<String,DictionaryElem> {
+    let mut out = HashMap::new();
+    
+    out.insert("curmudgeonly".to_string(), DictionaryElem{part_of_speech: PartOfSpeech::Adjective});
+    out.insert("family".to_string(), DictionaryElem{part_of_speech: PartOfSpeech::Noun});
+
+    //adjectives ending in 'ed'
+    for word in ["beloved", "naked", "ragged", "rugged", "sacred", "wicked"] {
+        out.insert(word.to_string(), DictionaryElem{part_of_speech: PartOfSpeech::Adjective});
+    }
+
+    //other words ending in 'ed' that aren't past participles
+    for word in ["bleed", "feed", "hundred", "need", "seed", "speed"] {
+        out.insert(word.to_string(), DictionaryElem{part_of_speech: PartOfSpeech::NotParticiple});
+    }
+
+    //irregular past participles
+    for word in ["beaten", "begun", "bitten", "blown", "born", "bought", "broken", "brought", "built", 
+        "caught", "chosen", "done", "drawn", "driven", "eaten", "fallen", "fed", "felt", "forbidden", 
+        "forgiven", "forgotten", "found", "frozen", "given", "grown", "heard", "held", "hidden", 
+        "hit", "hung", "hurt", "kept", "known", "laid", "led", "left", "lost", "made", "meant", "met", 
+        "paid", "put", "said", "seen", "sent", "set", "shaken", "shot", "shown", "shut", "sold", "spent", 
+        "spoken", "stolen", "struck", "sung", "taken", "taught", "thrown", "told", "torn", "thought", 
+        "understood", "won", "worn", "woken", "written"] {
+        out.insert(word.to_string(), DictionaryElem{part_of_speech: PartOfSpeech::PastParticiple});
+    }
+
+    out
+}
+
+pub fn init_database() -> HashMap<String, Vec<ActionTrigger>> {
+    let mut out: HashMap<String, Vec<ActionTrigger>> = HashMap::new();
+    out.insert("could".to_string(), 
+        vec![
+            ActionTrigger{action: Action::Filtering, trigger: vec!["hear".to_string()]},
+            ActionTrigger{action: Action::Filtering, trigger: vec!["see".to_string()]},
+            ActionTrigger{action: Action::Filtering, trigger: vec!["taste".to_string()]},
+        ]
+    );
+    out.insert("heard".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("listened".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("looked".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("saw".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("seemed".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("smelt".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("spotted".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("tasted".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+    out.insert("watched".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
+
+    out.insert("commenced".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
+    out.insert("began".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
+    out.insert("initiated".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
+    out.insert("started".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
+
+    out.insert("immediately".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![]}]);
+    out.insert("just".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![
+        "then".to_string()
+    ]}]);
+    out.insert("suddenly".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![]}]);
+
+    out.insert("are".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+    out.insert("can".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+    out.insert("do".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+    out.insert("has".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+    out.insert("have".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+    out.insert("i".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "am".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "have".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "will".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "would".to_string()
+        ]},
+    ]);
+    out.insert("it".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "is".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "will".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "would".to_string()
+        ]}
+    ]);
+    out.insert("there".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "is".to_string()
+        ]},
+    ]);
+    out.insert("they".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "are".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "have".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "will".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "would".to_string()
+        ]},
+    ]);
+    out.insert("you".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "are".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "have".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "will".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "would".to_string()
+        ]},
+    ]);
+    out.insert("we".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "are".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "have".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "will".to_string()
+        ]},
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "would".to_string()
+        ]},
+    ]);
+    out.insert("will".to_string(), vec![
+        ActionTrigger{action: Action::Contraction, trigger: vec![
+            "not".to_string()
+        ]},
+    ]);
+
+    out.insert("amazing".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("beautiful".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("bad".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("excellent".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("fantastic".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("good".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("great".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("lovely".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+    out.insert("wonderful".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
+
+    out
+}
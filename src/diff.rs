@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use crate::checker::{Action, Diagnostic};
+
+//findings are the same from one run to the next if the same rule found the
+//same words in the same sentence of the same file, wherever it's moved to
+fn key(diagnostic: &Diagnostic) -> (Action, &str, &str, &str) {
+    (diagnostic.action, &diagnostic.location.file, &diagnostic.text, &diagnostic.sentence)
+}
+
+/// How the findings changed between two runs over a manuscript.
+#[derive(Debug)]
+#[derive(Default)]
+pub struct FindingsDiff {
+    pub new: Vec<Diagnostic>,
+    pub resolved: Vec<Diagnostic>,
+    pub unchanged: Vec<Diagnostic>,
+}
+
+/// How many findings for one rule are new, resolved and unchanged.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(PartialEq)]
+pub struct DiffCounts {
+    pub new: usize,
+    pub resolved: usize,
+    pub unchanged: usize,
+}
+
+impl FindingsDiff {
+    pub fn between(previous: &[Diagnostic], current: &[Diagnostic]) -> FindingsDiff {
+        let mut unmatched: HashMap<_, Vec<&Diagnostic>> = HashMap::new();
+        for diagnostic in previous {
+            unmatched.entry(key(diagnostic)).or_default().push(diagnostic);
+        }
+
+        let mut out = FindingsDiff::default();
+        for diagnostic in current {
+            match unmatched.get_mut(&key(diagnostic)).and_then(|matches| matches.pop()) {
+                Some(_) => out.unchanged.push(diagnostic.clone()),
+                None => out.new.push(diagnostic.clone()),
+            }
+        }
+        //keep the resolved ones in document order
+        out.resolved = previous.iter()
+            .filter(|d| unmatched.get_mut(&key(d)).is_some_and(|matches| matches.pop().is_some()))
+            .cloned()
+            .collect();
+        out
+    }
+
+    /// The counts for each rule that has any findings, in rule order.
+    pub fn counts(&self) -> Vec<(Action, DiffCounts)> {
+        let mut counts: HashMap<Action, DiffCounts> = HashMap::new();
+        for d in &self.new {
+            counts.entry(d.action).or_default().new += 1;
+        }
+        for d in &self.resolved {
+            counts.entry(d.action).or_default().resolved += 1;
+        }
+        for d in &self.unchanged {
+            counts.entry(d.action).or_default().unchanged += 1;
+        }
+        Action::ALL.into_iter()
+            .filter_map(|action| counts.remove(&action).map(|c| (action, c)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::Checker;
+    use crate::config::Config;
+    use crate::markdown::parse_str;
+
+    fn check(text: &str) -> Vec<Diagnostic> {
+        let config = Config::default();
+        let document = parse_str(text, "test.md", &config);
+        Checker::with_rules(config, vec![Action::WeakImmediacy, Action::Echo]).check(&document)
+    }
+
+    #[test]
+    fn counts_for_each_rule() {
+        let previous = check("She was there just then. The crimson sky. Her crimson scarf.\n");
+        let current = check("The crimson sky. Her crimson scarf.\n\nHe left just then, and just then came back.\n");
+        let diff = FindingsDiff::between(&previous, &current);
+        assert_eq!(diff.counts(), vec![
+            (Action::WeakImmediacy, DiffCounts { new: 2, resolved: 1, unchanged: 0 }),
+            (Action::Echo, DiffCounts { new: 0, resolved: 0, unchanged: 1 }),
+        ]);
+        assert_eq!(diff.resolved[0].sentence, "She was there just then.");
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let findings = check("She was there just then. She was there just then.\n");
+        let diff = FindingsDiff::between(&findings, &findings);
+        assert!(diff.new.is_empty() && diff.resolved.is_empty());
+        assert_eq!(diff.counts(), vec![(Action::WeakImmediacy, DiffCounts { new: 0, resolved: 0, unchanged: 2 })]);
+    }
+
+    #[test]
+    fn a_repeated_finding_removed_once() {
+        let previous = check("She was there just then. She was there just then.\n");
+        let current = check("She was there just then.\n");
+        let diff = FindingsDiff::between(&previous, &current);
+        assert_eq!(diff.counts(), vec![(Action::WeakImmediacy, DiffCounts { new: 0, resolved: 1, unchanged: 1 })]);
+        assert!(FindingsDiff::between(&[], &[]).counts().is_empty());
+    }
+}
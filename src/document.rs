@@ -1,15 +1,139 @@
+use crate::error::TeastainError;
+use crate::suppress::Suppression;
+
+/// A range of a paragraph's text, in bytes and in characters.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Default)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl Span {
+    /// The span running from the start of this one to the end of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+            char_start: self.char_start,
+            char_end: other.char_end,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Word {
+    pub text: String,
+    pub span: Span,
+}
+
+impl Word {
+    /// A word that isn't in the paragraph text, e.g. a separator we add
+    /// when we join sentences together to report them.
+    pub fn synthetic(text: &str) -> Word {
+        Word { text: text.to_string(), span: Span::default() }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sentence {
+    pub words: Vec<Word>,
+    pub span: Span,
+    /// Which paragraph of the source file the sentence is in.
+    pub paragraph_index: usize,
+}
+
+impl Sentence {
+    pub fn new(words: Vec<Word>, paragraph_index: usize) -> Sentence {
+        let span = match (words.first(), words.last()) {
+            (Some(first), Some(last)) => first.span.to(&last.span),
+            _ => Span::default(),
+        };
+        Sentence { words, span, paragraph_index }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Speech {
+    pub sentences: Vec<Sentence>,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Paragraph {
+    /// Which paragraph of the source file this is, counting from 0, and
+    /// counting titles and section breaks.
+    pub index: usize,
+    pub text: String,
+    /// The file the paragraph came from.
+    pub file: String,
+    /// Where the paragraph is in the source file. For ODT this is the path
+    /// to its text:p element in content.xml.
+    pub source_path: String,
+    /// The byte offset the paragraph's text starts at in the source file,
+    /// for formats where the text is a run of the file, like Markdown. For
+    /// ODT, whose text is spread over XML elements, this is 0.
+    pub offset: usize,
+    /// Rules the author has asked not to be reported here.
+    pub suppressions: Vec<Suppression>,
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DocumentElement{
     ChapterTitle(String),
     ChapterName(String),
-    DirectSpeech(Vec<String>),
-    Sentence(Vec<String>),
+    ParagraphStart(Paragraph),
+    /// Speech, made up of its own sentences.
+    DirectSpeech(Speech),
+    /// Narration attached to the speech next to it, e.g. 'she said'.
+    SpeechTag(Sentence),
+    /// Comes just before speech whose quote is never closed, holding its
+    /// first sentence.
+    UnclosedQuote(Sentence),
+    Sentence(Sentence),
     ParagraphEnd,
     ChapterEnd,
     SectionEnd,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document{
     pub elements: Vec<DocumentElement>,
     pub number_of_chapters: i32,
+    /// Problems found reading the file that didn't stop us reading it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub warnings: Vec<TeastainError>,
+}
+
+impl Document {
+    /// The document as plain text, a paragraph to a line with blank lines
+    /// between them, and section breaks as '* * *'.
+    pub fn text(&self) -> String {
+        let mut blocks: Vec<&str> = vec![];
+        for element in &self.elements {
+            match element {
+                DocumentElement::ChapterTitle(text) | DocumentElement::ChapterName(text) => blocks.push(text),
+                DocumentElement::ParagraphStart(paragraph) => blocks.push(&paragraph.text),
+                DocumentElement::SectionEnd => blocks.push("* * *"),
+                _ => (),
+            }
+        }
+        let mut out = blocks.join("\n\n");
+        out.push('\n');
+        out
+    }
 }
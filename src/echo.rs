@@ -0,0 +1,132 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::config::Config;
+use crate::document::{DocumentElement, Word};
+use crate::frequency::sentences;
+
+pub(crate) const STOP_WORDS: &[&str] = &[
+    "about", "above", "after", "again", "against", "also", "been", "before", "being", "below",
+    "between", "both", "could", "does", "doing", "down", "during", "each", "even", "every", "from",
+    "further", "have", "having", "here", "herself", "himself", "into", "itself", "just", "like",
+    "more", "most", "much", "myself", "never", "only", "other", "ourselves", "over", "said", "same",
+    "should", "some", "such", "than", "that", "their", "theirs", "them", "themselves", "then",
+    "there", "these", "they", "this", "those", "through", "under", "until", "very", "were", "what",
+    "when", "where", "which", "while", "will", "with", "would", "your", "yours", "yourself",
+];
+
+/// Remembers the content words of the last few sentences of a scene, so that
+/// a distinctive word used again soon after can be reported. Echoes don't
+/// carry over a scene or chapter break.
+pub struct EchoTracker {
+    window: usize,
+    min_word_length: usize,
+    stop_words: HashSet<String>,
+    names: HashSet<String>,
+    recent: VecDeque<HashSet<String>>,
+}
+
+fn is_capitalised(word: &Word) -> bool {
+    word.text.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+//the names used in some document elements. A capital in the middle of a
+//sentence is almost always a name, and so is a word that only ever opens
+//sentences and is never written in lowercase
+pub(crate) fn names_in(elements: &[DocumentElement]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut openings = HashSet::new();
+    let mut lowercase = HashSet::new();
+    for sentence in sentences(elements) {
+        let words = sentence.iter().filter(|w| w.text.chars().any(|c| c.is_alphabetic()));
+        for (i, word) in words.enumerate() {
+            let lowercase_word = word.text.to_lowercase();
+            if !is_capitalised(word) {
+                lowercase.insert(lowercase_word);
+            } else if i == 0 {
+                openings.insert(lowercase_word);
+            } else {
+                names.insert(lowercase_word);
+            }
+        }
+    }
+    names.extend(openings.into_iter().filter(|w| !lowercase.contains(w)));
+    names
+}
+
+impl EchoTracker {
+    /// A tracker that ignores the given names, found by reading the whole
+    /// book first, and the configured ones.
+    pub fn new(config: &Config, names: &HashSet<String>) -> EchoTracker {
+        let mut names = names.clone();
+        names.extend(config.character_names.iter().map(|n| n.to_lowercase()));
+        EchoTracker {
+            window: config.echo_window,
+            min_word_length: config.echo_min_word_length,
+            stop_words: STOP_WORDS.iter().map(|w| w.to_string()).collect(),
+            names,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Forget the recent sentences; used at scene boundaries.
+    pub fn reset(&mut self) {
+        self.recent.clear();
+    }
+
+    /// Add the next sentence, returning the words in it that echo an earlier
+    /// one within the window. Each echoed word is returned once.
+    pub fn push_sentence(&mut self, sentence: &[Word]) -> Vec<Word> {
+        let mut out = vec![];
+        let mut seen: HashSet<String> = HashSet::new();
+
+        for word in sentence {
+            if !word.text.chars().any(|c| c.is_alphabetic()) {
+                continue;
+            }
+            let lowercase_word = word.text.to_lowercase();
+            if lowercase_word.chars().count() < self.min_word_length
+                || self.stop_words.contains(&lowercase_word)
+                || self.names.contains(&lowercase_word) {
+                continue;
+            }
+
+            let echoed = seen.contains(&lowercase_word)
+                || self.recent.iter().any(|s| s.contains(&lowercase_word));
+            if echoed && !out.iter().any(|w: &Word| w.text.to_lowercase() == lowercase_word) {
+                out.push(word.clone());
+            }
+            seen.insert(lowercase_word);
+        }
+
+        self.recent.push_back(seen);
+        while self.recent.len() > self.window {
+            self.recent.pop_front();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checker::{findings, Action};
+
+    fn echoes(text: &str) -> Vec<String> {
+        findings(text, Action::Echo)
+    }
+
+    #[test]
+    fn repeated_word() {
+        assert_eq!(echoes("The crimson sky. Her crimson scarf.\n"), vec!["crimson"]);
+    }
+
+    #[test]
+    fn names_opening_sentences() {
+        assert!(echoes("Delona waited. Delona looked up.\n").is_empty());
+        assert!(echoes("Harriet smiled at Harriet.\n").is_empty());
+    }
+
+    #[test]
+    fn opening_word_also_used_in_lowercase() {
+        assert_eq!(echoes("Shadows fell. The shadows grew.\n"), vec!["shadows"]);
+    }
+}
@@ -0,0 +1,67 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Everything that can go wrong reading a manuscript. Problems we can read
+/// past, like a paragraph with a style we don't know, are collected as
+/// warnings on the document rather than returned.
+#[derive(Debug)]
+pub enum TeastainError {
+    Io(io::Error),
+    Zip(zip::result::ZipError),
+    /// Malformed XML, with the byte position in the file it was found at.
+    Xml {
+        position: usize,
+        error: quick_xml::Error,
+    },
+    /// An ODT file without the content.xml that holds its text.
+    MissingContent,
+    /// A paragraph uses a style the document doesn't define.
+    UnknownStyle(String),
+    /// Something that went wrong with one of the files that make up a book.
+    File {
+        path: PathBuf,
+        error: Box<TeastainError>,
+    },
+    /// A suppression comment naming a rule or scope we don't know.
+    InvalidSuppression(String),
+    /// A file we don't know how to read, by its extension, e.g. "docx".
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for TeastainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TeastainError::Io(e) => write!(f, "I/O error: {}", e),
+            TeastainError::Zip(e) => write!(f, "could not read zip archive: {}", e),
+            TeastainError::Xml { position, error } => write!(f, "XML error at position {}: {}", position, error),
+            TeastainError::MissingContent => write!(f, "no content.xml in ODT file"),
+            TeastainError::UnknownStyle(name) => write!(f, "unknown style '{}'", name),
+            TeastainError::InvalidSuppression(word) => write!(f, "unknown rule or scope '{}' in teastain:ignore", word),
+            TeastainError::UnsupportedFormat(name) => write!(f, "unsupported file format '{}'", name),
+            TeastainError::File { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for TeastainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TeastainError::Io(e) => Some(e),
+            TeastainError::Zip(e) => Some(e),
+            TeastainError::Xml { error, .. } => Some(error),
+            TeastainError::File { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TeastainError {
+    fn from(e: io::Error) -> TeastainError {
+        TeastainError::Io(e)
+    }
+}
+
+impl From<zip::result::ZipError> for TeastainError {
+    fn from(e: zip::result::ZipError) -> TeastainError {
+        TeastainError::Zip(e)
+    }
+}
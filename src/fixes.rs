@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use crate::checker::{Action, Diagnostic};
+use crate::config::Config;
+use crate::document::Span;
+use crate::error::TeastainError;
+use crate::format::Format;
+use crate::{markdown, odt};
+
+/// The contraction of a phrase the contraction rule reports, like "don’t"
+/// for "do not", keeping the case of its first letter, or of the whole
+/// phrase if it's in capitals.
+pub fn contraction(phrase: &str, apostrophe: &str) -> Option<String> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    let [first, second] = words[..] else {
+        return None;
+    };
+    let lowercase_first = first.to_lowercase();
+    let contracted = match (lowercase_first.as_str(), second.to_lowercase().as_str()) {
+        ("will", "not") => format!("won{}t", apostrophe),
+        ("can", "not") => format!("can{}t", apostrophe),
+        (word, "not") => format!("{}n{}t", word, apostrophe),
+        (word, "am") => format!("{}{}m", word, apostrophe),
+        (word, "are") => format!("{}{}re", word, apostrophe),
+        (word, "have") => format!("{}{}ve", word, apostrophe),
+        (word, "is") => format!("{}{}s", word, apostrophe),
+        (word, "will") => format!("{}{}ll", word, apostrophe),
+        (word, "would") => format!("{}{}d", word, apostrophe),
+        _ => return None,
+    };
+
+    if !phrase.chars().any(char::is_lowercase) {
+        Some(contracted.to_uppercase())
+    } else if first.starts_with(char::is_uppercase) {
+        let mut chars = contracted.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect())
+    } else {
+        Some(contracted)
+    }
+}
+
+/// What to replace the words a rule found with, for the rules that know.
+pub fn suggestion(action: Action, text: &str, config: &Config) -> Option<String> {
+    match action {
+        Action::Contraction => contraction(text, config.quote_convention.apostrophe()),
+        _ => None,
+    }
+}
+
+/// A change to a manuscript that resolves a finding.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Fix {
+    /// Where the paragraph is in the source file, as in a finding's
+    /// location.
+    pub source_path: String,
+    /// The byte offset the paragraph starts at in the source file.
+    pub offset: usize,
+    /// The part of the paragraph's text to replace.
+    pub span: Span,
+    /// The text that was found there.
+    pub original: String,
+    pub replacement: String,
+}
+
+impl Fix {
+    /// The fix for a finding that has a suggestion.
+    pub fn of(diagnostic: &Diagnostic) -> Option<Fix> {
+        let replacement = diagnostic.suggestion.clone()?;
+        Some(Fix {
+            source_path: diagnostic.location.source_path.clone(),
+            offset: diagnostic.location.offset,
+            span: diagnostic.location.span,
+            original: diagnostic.text.clone(),
+            replacement,
+        })
+    }
+
+    fn overlaps(&self, other: &Fix) -> bool {
+        self.source_path == other.source_path
+            && self.offset == other.offset
+            && self.span.start < other.span.end
+            && other.span.start < self.span.end
+    }
+}
+
+//the fixes that can still be made, in the order they come in the file:
+//ones whose text, as `text_of` finds it, is what was found, and that don't
+//overlap one before them
+pub(crate) fn usable<'a, 't>(fixes: &'a [Fix], text_of: impl Fn(&Fix) -> Option<&'t str>) -> Vec<&'a Fix> {
+    let mut sorted: Vec<&Fix> = fixes.iter().collect();
+    sorted.sort_by_key(|fix| (fix.offset, fix.source_path.clone(), fix.span.start));
+    let mut out: Vec<&Fix> = vec![];
+    for fix in sorted {
+        if text_of(fix) == Some(fix.original.as_str()) && !out.iter().any(|done| done.overlaps(fix)) {
+            out.push(fix);
+        }
+    }
+    out
+}
+
+/// Make fixes to a manuscript, rewriting the file. Fixes to text that has
+/// changed since it was checked are skipped. Returns how many were made.
+pub fn apply(path: &Path, fixes: &[Fix]) -> Result<usize, TeastainError> {
+    match Format::of(path) {
+        Some(Format::Odt) => odt::apply_fixes(path, fixes),
+        Some(Format::Markdown) => markdown::apply_fixes(path, fixes),
+        None => {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            Err(TeastainError::UnsupportedFormat(extension.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contractions() {
+        let cases = [
+            ("do not", "'", Some("don't")),
+            ("do not", "\u{2019}", Some("don\u{2019}t")),
+            ("Do not", "'", Some("Don't")),
+            ("DO NOT", "'", Some("DON'T")),
+            ("will not", "'", Some("won't")),
+            ("Will not", "\u{2019}", Some("Won\u{2019}t")),
+            ("can not", "'", Some("can't")),
+            ("I am", "'", Some("I'm")),
+            ("I am", "\u{2019}", Some("I\u{2019}m")),
+            ("They are", "'", Some("They're")),
+            ("we have", "\u{2019}", Some("we\u{2019}ve")),
+            ("It is", "'", Some("It's")),
+            ("she will", "'", Some("she'll")),
+            ("He would", "\u{2019}", Some("He\u{2019}d")),
+            ("she was", "'", None),
+            ("not", "'", None),
+            ("I do not", "'", None),
+        ];
+        for (phrase, apostrophe, expected) in cases {
+            assert_eq!(contraction(phrase, apostrophe).as_deref(), expected, "{}", phrase);
+        }
+    }
+}
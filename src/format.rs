@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use crate::config::Config;
+use crate::document::Document;
+use crate::error::TeastainError;
+use crate::{markdown, odt};
+
+/// The kinds of file teastain can read.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum Format {
+    Odt,
+    /// Markdown, or plain text, which reads the same way.
+    Markdown,
+}
+
+impl Format {
+    /// The format of a file, by its extension.
+    pub fn of(path: &Path) -> Option<Format> {
+        let extension = path.extension().and_then(|e| e.to_str())?.to_lowercase();
+        match extension.as_str() {
+            "odt" => Some(Format::Odt),
+            "md" | "markdown" | "txt" | "text" => Some(Format::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Read a manuscript in any format we know.
+pub fn parse(path: &Path, config: &Config) -> Result<Document, TeastainError> {
+    match Format::of(path) {
+        Some(Format::Odt) => odt::parse(path, config),
+        Some(Format::Markdown) => markdown::parse(path, config),
+        None => {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            Err(TeastainError::UnsupportedFormat(extension.to_string()))
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::{collections::{HashMap, HashSet}, sync::OnceLock};
+
+use crate::config::Config;
+use crate::document::{Document, DocumentElement, Word};
+use crate::echo::STOP_WORDS;
+
+//function words too short to be among the echo rule's stop words
+const SHORT_STOP_WORDS: &[&str] = &[
+    "a", "all", "an", "and", "any", "are", "as", "at", "be", "but", "by", "can", "did", "do", "for",
+    "had", "has", "he", "her", "him", "his", "how", "i", "if", "in", "is", "it", "its", "me", "my",
+    "no", "nor", "not", "of", "off", "on", "or", "our", "out", "own", "she", "so", "the", "to",
+    "too", "up", "us", "was", "we", "who", "why", "you",
+];
+
+//a word has to be used at least this often in a chapter or book before
+//it's worth calling overused
+const MIN_OVERUSED_COUNT: usize = 5;
+
+const ENGLISH_FREQUENCIES: &str = include_str!("../res/word_frequencies.txt");
+
+/// How often words are used in English fiction, per million words.
+pub fn english_rates() -> &'static HashMap<&'static str, f64> {
+    static RATES: OnceLock<HashMap<&'static str, f64>> = OnceLock::new();
+    RATES.get_or_init(|| {
+        ENGLISH_FREQUENCIES.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let (word, rate) = line.split_once(' ')?;
+                Some((word, rate.trim().parse().ok()?))
+            })
+            .collect()
+    })
+}
+
+//the endings of contractions, so that 'it's' and 'didn't' count as the
+//function words they are
+const CONTRACTION_ENDINGS: &[&str] = &["n't", "'s", "'m", "'re", "'ll", "'ve", "'d"];
+//contractions whose first part isn't a word on its own
+const IRREGULAR_CONTRACTIONS: &[&str] = &["ain't", "can't", "shan't", "won't"];
+
+fn is_stop_word(word: &str) -> bool {
+    if IRREGULAR_CONTRACTIONS.contains(&word) {
+        return true;
+    }
+    let stem = CONTRACTION_ENDINGS.iter().find_map(|ending| word.strip_suffix(ending)).unwrap_or(word);
+    SHORT_STOP_WORDS.contains(&stem) || STOP_WORDS.contains(&stem)
+}
+
+//the word as it's counted: lowercase, with a straight apostrophe
+fn normalise(word: &str) -> String {
+    word.to_lowercase().replace('’', "'")
+}
+
+fn is_word(word: &Word) -> bool {
+    word.text.chars().any(|c| c.is_alphabetic())
+}
+
+/// A word, how often it's used, and how that compares with English in
+/// general.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordCount {
+    pub word: String,
+    pub count: usize,
+    pub per_10k_words: f64,
+    /// How many times more often than in English in general, for words we
+    /// have a rate for.
+    pub times_normal: Option<f64>,
+}
+
+/// A run of words and how often it's used.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NgramCount {
+    pub ngram: String,
+    pub count: usize,
+}
+
+/// Counts the words, and runs of two to four words, in part of a
+/// manuscript.
+pub struct WordCounter<'a> {
+    config: &'a Config,
+    /// All the words, content and otherwise.
+    pub words: usize,
+    counts: HashMap<String, usize>,
+    ngrams: HashMap<String, usize>,
+    //words capitalised in the middle of a sentence, which are names
+    names: HashSet<String>,
+}
+
+impl<'a> WordCounter<'a> {
+    pub fn new(config: &'a Config) -> WordCounter<'a> {
+        WordCounter {
+            config,
+            words: 0,
+            counts: HashMap::new(),
+            ngrams: HashMap::new(),
+            names: config.character_names.iter().map(|n| normalise(n)).collect(),
+        }
+    }
+
+    pub fn add_sentence(&mut self, sentence: &[Word]) {
+        let words: Vec<&Word> = sentence.iter().filter(|w| is_word(w)).collect();
+        let normalised: Vec<String> = words.iter().map(|w| normalise(&w.text)).collect();
+        self.words += words.len();
+
+        for (i, (word, text)) in words.iter().zip(&normalised).enumerate() {
+            if i > 0 && word.text.starts_with(char::is_uppercase) {
+                self.names.insert(text.clone());
+            }
+            *self.counts.entry(text.clone()).or_default() += 1;
+        }
+        for size in 2..=4 {
+            for window in normalised.windows(size) {
+                if window.iter().all(|w| is_stop_word(w)) {
+                    continue;
+                }
+                *self.ngrams.entry(window.join(" ")).or_default() += 1;
+            }
+        }
+    }
+
+    fn word_count(&self, word: &str, count: usize) -> WordCount {
+        let per_10k_words = if self.words == 0 { 0.0 } else { count as f64 * 10_000.0 / self.words as f64 };
+        let times_normal = english_rates().get(word).map(|per_million| per_10k_words * 100.0 / per_million);
+        WordCount { word: word.to_string(), count, per_10k_words, times_normal }
+    }
+
+    //the content words, most used first
+    fn content_words(&self) -> Vec<WordCount> {
+        let mut out: Vec<WordCount> = self.counts.iter()
+            .filter(|(word, _)| word.chars().count() > 2 && !is_stop_word(word) && !self.names.contains(*word))
+            .map(|(word, count)| self.word_count(word, *count))
+            .collect();
+        out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+        out
+    }
+
+    /// The most used content words, leaving out names and function words.
+    pub fn top_words(&self, limit: usize) -> Vec<WordCount> {
+        self.content_words().into_iter().take(limit).collect()
+    }
+
+    /// The most used runs of `size` words that are used more than once.
+    pub fn top_ngrams(&self, size: usize, limit: usize) -> Vec<NgramCount> {
+        let mut out: Vec<NgramCount> = self.ngrams.iter()
+            .filter(|(ngram, count)| **count > 1 && ngram.split(' ').count() == size)
+            .map(|(ngram, count)| NgramCount { ngram: ngram.clone(), count: *count })
+            .collect();
+        out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.ngram.cmp(&b.ngram)));
+        out.truncate(limit);
+        out
+    }
+
+    /// Whether a word is used more than the overused word rule allows: more
+    /// often than the configured rate, and either one of the configured
+    /// words or used far more than in English in general.
+    pub fn is_overused(&self, word_count: &WordCount) -> bool {
+        let config = self.config;
+        word_count.count >= MIN_OVERUSED_COUNT
+            && word_count.per_10k_words >= config.overused_word_rate
+            && (config.overused_words.contains(&word_count.word)
+                || word_count.times_normal.is_some_and(|times| times >= config.overused_word_ratio))
+    }
+
+    /// The overused words, most overused first.
+    pub fn overused(&self) -> Vec<WordCount> {
+        let mut out: Vec<WordCount> = self.content_words().into_iter().filter(|w| self.is_overused(w)).collect();
+        out.sort_by(|a, b| b.per_10k_words.total_cmp(&a.per_10k_words));
+        out
+    }
+}
+
+//the sentences in some document elements, speech included
+pub(crate) fn sentences(elements: &[DocumentElement]) -> Vec<&[Word]> {
+    let mut out = vec![];
+    for element in elements {
+        match element {
+            DocumentElement::Sentence(sentence) | DocumentElement::SpeechTag(sentence) => out.push(&sentence.words[..]),
+            DocumentElement::DirectSpeech(speech) => out.extend(speech.sentences.iter().map(|s| &s.words[..])),
+            _ => (),
+        }
+    }
+    out
+}
+
+fn counter_for<'a>(elements: &[DocumentElement], config: &'a Config) -> WordCounter<'a> {
+    let mut counter = WordCounter::new(config);
+    for sentence in sentences(elements) {
+        counter.add_sentence(sentence);
+    }
+    counter
+}
+
+/// The words overused across a whole book, though maybe not in any one
+/// chapter of it.
+pub(crate) fn overused_in_book(elements: &[DocumentElement], config: &Config) -> HashSet<String> {
+    counter_for(elements, config).overused().into_iter().map(|w| w.word).collect()
+}
+
+/// The words the overused word rule reports in a chapter, and how often
+/// each is used in it: those overused in the chapter, and those overused
+/// in the book that the chapter uses.
+pub(crate) fn overused_in(elements: &[DocumentElement], config: &Config, in_book: &HashSet<String>) -> HashMap<String, usize> {
+    let counter = counter_for(elements, config);
+    counter.content_words().into_iter()
+        .filter(|w| counter.is_overused(w) || in_book.contains(&w.word))
+        .map(|w| (w.word, w.count))
+        .collect()
+}
+
+/// If a word in a sentence is one of those found by `overused_in`, the
+/// word as it's counted.
+pub(crate) fn overused_word(word: &Word, overused: &HashMap<String, usize>) -> Option<String> {
+    if !is_word(word) {
+        return None;
+    }
+    let word = normalise(&word.text);
+    overused.contains_key(&word).then_some(word)
+}
+
+/// The words used most in a chapter, and the ones used too much.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChapterWords {
+    pub number: i32,
+    pub title: String,
+    pub words: usize,
+    pub top_words: Vec<WordCount>,
+    pub overused: Vec<WordCount>,
+}
+
+/// The words and runs of words a manuscript uses most, and the ones it
+/// uses more than it should, overall and by chapter.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrequencyReport {
+    pub words: usize,
+    pub top_words: Vec<WordCount>,
+    /// The most used runs of two, three and four words.
+    pub ngrams: Vec<Vec<NgramCount>>,
+    pub overused: Vec<WordCount>,
+    pub chapters: Vec<ChapterWords>,
+}
+
+impl FrequencyReport {
+    /// The report for a document, with up to `limit` words and runs of
+    /// words in each list.
+    pub fn of(document: &Document, config: &Config, limit: usize) -> FrequencyReport {
+        let mut chapters = vec![];
+        let chapter_elements = document.elements.split_inclusive(|e| matches!(e, DocumentElement::ChapterEnd));
+        for (i, elements) in chapter_elements.enumerate() {
+            let mut counter = WordCounter::new(config);
+            let mut title = String::from("");
+            for element in elements {
+                match element {
+                    DocumentElement::ChapterTitle(text) => title = text.clone(),
+                    DocumentElement::ChapterName(text) if title.is_empty() => title = text.clone(),
+                    _ => (),
+                }
+            }
+            for sentence in sentences(elements) {
+                counter.add_sentence(sentence);
+            }
+            chapters.push(ChapterWords {
+                number: i as i32 + 1,
+                title,
+                words: counter.words,
+                top_words: counter.top_words(limit),
+                overused: counter.overused(),
+            });
+        }
+
+        let mut counter = WordCounter::new(config);
+        for sentence in sentences(&document.elements) {
+            counter.add_sentence(sentence);
+        }
+        FrequencyReport {
+            words: counter.words,
+            top_words: counter.top_words(limit),
+            ngrams: (2..=4).map(|size| counter.top_ngrams(size, limit)).collect(),
+            overused: counter.overused(),
+            chapters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::Action;
+
+    #[test]
+    fn contractions_of_stop_words_are_stop_words() {
+        for word in ["it's", "didn't", "i'm", "they're", "we'll", "could've", "she'd", "won't", "can't"] {
+            assert!(is_stop_word(word), "{}", word);
+        }
+        assert!(!is_stop_word("collar"));
+        assert!(!is_stop_word("delona's"));
+    }
+
+    #[test]
+    fn an_overused_word_is_reported_once_a_chapter_with_its_count() {
+        let text = "He nodded. She nodded. They nodded.\n\nWe nodded. You nodded.\n";
+        let found = crate::checker::check_markdown(text, Action::OverusedWord, Config::default());
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert_eq!(found[0].text, "nodded");
+        assert_eq!(found[0].count, Some(5));
+        assert_eq!(found[0].location.sentence_number, 1);
+    }
+
+    #[test]
+    fn common_words_are_not_overused() {
+        let text = "Another day went away. He replied to another letter and walked away. \
+            She replied, then another knock sent him away. Another replied from far away.";
+        assert!(crate::checker::findings(text, Action::OverusedWord).is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+//! Teastain reads a manuscript and reports the weak spots in its prose:
+//! filtering, adverbs, passive voice, echoes, said-bookisms and the like.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use teastain::{parse, Checker, Config};
+//!
+//! let config = Config::default();
+//! let document = parse(Path::new("novel.odt"), &config)?;
+//! for diagnostic in Checker::new(config).check(&document) {
+//!     println!("{}", diagnostic);
+//! }
+//! # Ok::<(), teastain::TeastainError>(())
+//! ```
+
+pub mod baseline;
+pub mod book;
+pub mod charts;
+pub mod checker;
+pub mod config;
+pub mod diff;
+pub mod document;
+pub mod error;
+pub mod fixes;
+pub mod format;
+pub mod frequency;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod markdown;
+pub mod odt;
+pub mod quotes;
+pub mod readability;
+#[cfg(feature = "tui")]
+pub mod review;
+pub mod stats;
+pub mod suppress;
+
+mod builder;
+mod database;
+mod echo;
+mod matcher;
+mod openings;
+mod passive;
+mod tags;
+
+pub use checker::{Action, Checker, Diagnostic, Location};
+pub use config::Config;
+pub use document::{Document, DocumentElement};
+pub use error::TeastainError;
+pub use format::parse;
+pub use stats::Stats;
@@ -0,0 +1,275 @@
+use std::{collections::HashMap, error::Error};
+
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{self as lsp_notification, DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, PublishDiagnostics},
+    request::{self as lsp_request, CodeActionRequest},
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability,
+    DiagnosticSeverity, NumberOrString, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::checker::{Checker, Diagnostic};
+use crate::markdown;
+
+/// Turns byte offsets in a buffer into LSP positions, which count UTF-16
+/// code units.
+struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> LineIndex<'a> {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { text, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = self.text.get(line_start..offset).map_or(0, |s| s.encode_utf16().count());
+        Position::new(line as u32, character as u32)
+    }
+
+    fn range(&self, diagnostic: &Diagnostic) -> Range {
+        let location = &diagnostic.location;
+        Range::new(
+            self.position(location.offset + location.span.start),
+            self.position(location.offset + location.span.end),
+        )
+    }
+}
+
+fn to_lsp(diagnostic: &Diagnostic, lines: &LineIndex) -> lsp_types::Diagnostic {
+    lsp_types::Diagnostic {
+        range: lines.range(diagnostic),
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        code: Some(NumberOrString::String(diagnostic.action.name().to_string())),
+        source: Some(String::from("teastain")),
+        message: match diagnostic.count {
+            Some(count) => format!("{}: {}, used {} times in the chapter", diagnostic.action.description(), diagnostic.text, count),
+            None => format!("{}: {}", diagnostic.action.description(), diagnostic.text),
+        },
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+fn notification_params<N: lsp_notification::Notification>(notification: Notification) -> serde_json::Result<N::Params> {
+    serde_json::from_value(notification.params)
+}
+
+fn overlaps(a: &Range, b: &Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Lints the buffers an editor has open, as Markdown.
+struct Server {
+    connection: Connection,
+    checker: Checker,
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn check(&self, uri: &Uri, text: &str) -> Vec<Diagnostic> {
+        let document = markdown::parse_str(text, uri.as_str(), self.checker.config());
+        self.checker.check(&document)
+    }
+
+    fn publish(&self, uri: Uri, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        let method = <PublishDiagnostics as lsp_notification::Notification>::METHOD;
+        let notification = Notification::new(String::from(method), params);
+        self.connection.sender.send(Message::Notification(notification))?;
+        Ok(())
+    }
+
+    fn update(&mut self, uri: Uri, text: String) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lines = LineIndex::new(&text);
+        let diagnostics = self.check(&uri, &text).iter().map(|d| to_lsp(d, &lines)).collect();
+        self.documents.insert(uri.clone(), text);
+        self.publish(uri, diagnostics)
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Box<dyn Error + Send + Sync>> {
+        use lsp_notification::Notification as _;
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification_params::<DidOpenTextDocument>(notification)?;
+                self.update(params.text_document.uri, params.text_document.text)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification_params::<DidChangeTextDocument>(notification)?;
+                //we ask for whole buffers, so the last change is the lot
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.update(params.text_document.uri, change.text)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification_params::<DidCloseTextDocument>(notification)?;
+                self.documents.remove(&params.text_document.uri);
+                self.publish(params.text_document.uri, vec![])?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn code_actions(&self, params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = params.text_document.uri;
+        let Some(text) = self.documents.get(&uri) else {
+            return vec![];
+        };
+        let lines = LineIndex::new(text);
+
+        let mut out = vec![];
+        for diagnostic in self.check(&uri, text) {
+            let range = lines.range(&diagnostic);
+            if !overlaps(&range, &params.range) {
+                continue;
+            }
+            let Some(fix) = diagnostic.suggestion.clone() else {
+                continue;
+            };
+            let edit = TextEdit { range, new_text: fix.clone() };
+            out.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with '{}'", fix),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![to_lsp(&diagnostic, &lines)]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..WorkspaceEdit::default()
+                }),
+                is_preferred: Some(true),
+                ..CodeAction::default()
+            }));
+        }
+        out
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), Box<dyn Error + Send + Sync>> {
+        use lsp_request::Request as _;
+        let response = match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = serde_json::from_value(request.params)?;
+                Response::new_ok(request.id, self.code_actions(params))
+            }
+            _ => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unknown request '{}'", request.method),
+            ),
+        };
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+}
+
+/// Serve LSP over a connection until the client shuts us down.
+pub fn serve(connection: Connection, checker: Checker) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server { connection, checker, documents: HashMap::new() };
+    while let Ok(message) = server.connection.receiver.recv() {
+        match message {
+            Message::Request(request) => {
+                if server.connection.handle_shutdown(&request)? {
+                    break;
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => (),
+        }
+    }
+    Ok(())
+}
+
+/// Serve LSP over stdin and stdout.
+pub fn run(checker: Checker) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(connection, checker)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use lsp_server::RequestId;
+    use serde_json::json;
+
+    use super::*;
+    use crate::checker::Action;
+    use crate::config::Config;
+
+    const URI: &str = "file:///book.md";
+
+    fn receive(client: &Connection) -> Message {
+        client.receiver.recv_timeout(Duration::from_secs(10)).unwrap()
+    }
+
+    fn request(client: &Connection, id: i32, method: &str, params: serde_json::Value) -> serde_json::Value {
+        let request = Request::new(RequestId::from(id), String::from(method), params);
+        client.sender.send(Message::Request(request)).unwrap();
+        match receive(client) {
+            Message::Response(response) => response.response_result.unwrap(),
+            message => panic!("expected a response, got {:?}", message),
+        }
+    }
+
+    fn notify(client: &Connection, method: &str, params: serde_json::Value) {
+        let notification = Notification::new(String::from(method), params);
+        client.sender.send(Message::Notification(notification)).unwrap();
+    }
+
+    #[test]
+    fn diagnostics_and_quick_fix() {
+        let (server, client) = Connection::memory();
+        let checker = Checker::with_rules(Config::default(), vec![Action::Contraction]);
+        let server = thread::spawn(move || serve(server, checker).unwrap());
+
+        request(&client, 1, "initialize", json!({ "capabilities": {} }));
+        notify(&client, "initialized", json!({}));
+
+        //'é' is two bytes but one UTF-16 code unit
+        let text = "The café was shut.\nAnd I do not know why.\n";
+        notify(&client, "textDocument/didOpen", json!({
+            "textDocument": { "uri": URI, "languageId": "markdown", "version": 1, "text": text },
+        }));
+        let Message::Notification(notification) = receive(&client) else {
+            panic!("expected diagnostics");
+        };
+        assert_eq!(notification.method, "textDocument/publishDiagnostics");
+        let params: PublishDiagnosticsParams = serde_json::from_value(notification.params).unwrap();
+        assert_eq!(params.diagnostics.len(), 1);
+        let range = params.diagnostics[0].range;
+        assert_eq!(range, Range::new(Position::new(1, 6), Position::new(1, 12)));
+
+        let response = request(&client, 2, "textDocument/codeAction", json!({
+            "textDocument": { "uri": URI },
+            "range": range,
+            "context": { "diagnostics": [] },
+        }));
+        let actions: Vec<CodeActionOrCommand> = serde_json::from_value(response).unwrap();
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        let uri: Uri = URI.parse().unwrap();
+        let edits = action.edit.as_ref().and_then(|edit| edit.changes.as_ref()?.get(&uri)).unwrap();
+        assert_eq!(edits, &vec![TextEdit { range, new_text: String::from("don’t") }]);
+
+        request(&client, 3, "shutdown", json!(null));
+        notify(&client, "exit", json!(null));
+        server.join().unwrap();
+    }
+}
@@ -1,468 +1,934 @@
-use std::{path::Path, collections::HashMap, env};
+use std::{fs, io::{self, Write}, path::{Path, PathBuf}, process::ExitCode, thread, time::{Duration, SystemTime}};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use teastain::{parse, Action, Checker, Config, Diagnostic, Document, Stats};
+use teastain::baseline::Baseline;
+use teastain::book::{self, Manifest, MANIFEST_NAME};
+use teastain::charts;
+use teastain::diff::FindingsDiff;
+use teastain::fixes::{self, Fix};
+use teastain::frequency::{FrequencyReport, WordCount};
+use teastain::quotes::QuoteConvention;
+use teastain::config::ReadingAgeBand;
+use teastain::readability::Readability;
+use teastain::stats::{ChapterStats, Counts};
+use teastain::review::{self, Finding, Review, ReviewProgress, ReviewStatus};
+
+//exit codes, besides 0 for success
+const EXIT_FINDINGS: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+
+//how often --watch looks at the files
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+//print! and println!, except that when whatever's reading the output stops
+//early, like `head`, we stop quietly too rather than panicking
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if let Err(e) = write!(io::stdout(), $($arg)*) {
+            stop_writing(e);
+        }
+    };
+}
 
-use document::{Document, DocumentElement};
-use odt::parse;
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        if let Err(e) = writeln!(io::stdout(), $($arg)*) {
+            stop_writing(e);
+        }
+    };
+}
 
+fn stop_writing(e: io::Error) -> ! {
+    if e.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("error: {}", e);
+        std::process::exit(EXIT_ERROR.into());
+    }
+    std::process::exit(0)
+}
 
-mod odt;
-mod document;
+//the exit code for a command that's done, having maybe failed to read
+//some of its files
+fn exit_code(failed: bool) -> ExitCode {
+    if failed { ExitCode::from(EXIT_ERROR) } else { ExitCode::SUCCESS }
+}
 
-fn loc_string(
-    chapter_title: &String, 
-    chapter_number: i32, 
-    section_number: i32, 
-    paragraph_number: i32,
-    number_of_chapters: i32
-) -> String {
-    let mut out = String::from("");
-    if chapter_title != "" {
-        out += chapter_title;
-        out += ", ";
-    } else if number_of_chapters > 1 {
-        out += "Chapter ";
-        out += &chapter_number.to_string();
-        out += ", ";
-    }
-    out += "Section ";
-    out += &section_number.to_string();
-    out += ", ";
-    out += "Paragraph ";
-    out += &paragraph_number.to_string();
+/// Finds the weak spots in a manuscript's prose.
+#[derive(Parser)]
+#[command(name = "teastain", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-    out
+#[derive(Subcommand)]
+enum Command {
+    /// Check manuscripts and report what's found
+    Check(CheckArgs),
+    /// Count the chapters, paragraphs, sentences and words in manuscripts
+    Stats(StatsArgs),
+    /// Print manuscripts as plain text
+    ExportText(InputArgs),
+    /// Draw charts of the pacing of manuscripts, as SVG files
+    Charts(ChartsArgs),
+    /// List the words and phrases manuscripts use most, and the ones they
+    /// overuse
+    Words(WordsArgs),
+    /// Go through the findings in the terminal, accepting, ignoring or
+    /// fixing each one
+    Review(ReviewArgs),
+    /// Serve diagnostics to an editor, over the Language Server Protocol on
+    /// stdin and stdout. Buffers are read as Markdown
+    Lsp(SettingsArgs),
+    /// List and explain the rules
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
 }
 
-fn sentence_string(words: &Vec<String>) -> String {
-    words.concat()
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// List every rule
+    List,
+    /// Explain what a rule looks for
+    Explain {
+        #[arg(value_parser = parse_rule)]
+        rule: Action,
+    },
 }
 
-#[derive(Debug)]
-#[derive(Clone)]
-#[derive(Copy)]
-#[derive(PartialEq)]
-enum Action{
-    Filtering,
-    Beginning,
-    WeakImmediacy,
-    PotentialAdverb,
-    Adverb,
-    Contraction,
-    SubjectiveAdjective,
+#[derive(Args)]
+struct InputArgs {
+    /// Files to read. Globs like 'chapters/*.odt' are expanded. A directory,
+    /// or a book.toml listing files, is read as one book, a chapter to a file
+    #[arg(required = true)]
+    files: Vec<String>,
+    #[command(flatten)]
+    settings: SettingsArgs,
 }
 
-struct ActionTrigger{
-    pub action: Action,
-    pub trigger: Vec<String>
+#[derive(Args)]
+struct SettingsArgs {
+    /// A TOML file of settings
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// The quotation marks the manuscript uses for speech: uk, us, straight
+    /// or guillemets
+    #[arg(long, value_parser = parse_quotes)]
+    quotes: Option<QuoteConvention>,
 }
 
-#[derive(Debug)]
 #[derive(Clone)]
 #[derive(Copy)]
-#[derive(PartialEq)]
-enum PartOfSpeech{
-    Adjective,
-    Adverb,
-    Noun,
-}
-
-#[derive(Debug)]
-struct DictionaryElem{
-    pub part_of_speech: PartOfSpeech
-}
-
-fn init_dictionary() -> HashMap<String,DictionaryElem> {
-    let mut out = HashMap::new();
-    
-    out.insert("curmudgeonly".to_string(), DictionaryElem{part_of_speech: PartOfSpeech::Adjective});
-    out.insert("family".to_string(), DictionaryElem{part_of_speech: PartOfSpeech::Noun});
-
-    out
-}
-
-fn init_database() -> HashMap<String, Vec<ActionTrigger>> {
-    let mut out: HashMap<String, Vec<ActionTrigger>> = HashMap::new();
-    out.insert("could".to_string(), 
-        vec![
-            ActionTrigger{action: Action::Filtering, trigger: vec!["hear".to_string()]},
-            ActionTrigger{action: Action::Filtering, trigger: vec!["see".to_string()]},
-            ActionTrigger{action: Action::Filtering, trigger: vec!["taste".to_string()]},
-        ]
-    );
-    out.insert("heard".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("listened".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("looked".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("saw".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("seemed".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("smelt".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("spotted".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("tasted".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-    out.insert("watched".to_string(), vec![ActionTrigger{action: Action::Filtering, trigger: vec![]}]);
-
-    out.insert("commenced".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
-    out.insert("began".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
-    out.insert("initiated".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
-    out.insert("started".to_string(), vec![ActionTrigger{action: Action::Beginning, trigger: vec![]}]);
-
-    out.insert("immediately".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![]}]);
-    out.insert("just".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![
-        "then".to_string()
-    ]}]);
-    out.insert("suddenly".to_string(), vec![ActionTrigger{action: Action::WeakImmediacy, trigger: vec![]}]);
-
-    out.insert("are".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-    out.insert("can".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-    out.insert("do".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-    out.insert("has".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-    out.insert("have".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-    out.insert("i".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "am".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "have".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "will".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "would".to_string()
-        ]},
-    ]);
-    out.insert("it".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "is".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "will".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "would".to_string()
-        ]}
-    ]);
-    out.insert("there".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "is".to_string()
-        ]},
-    ]);
-    out.insert("they".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "are".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "have".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "will".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "would".to_string()
-        ]},
-    ]);
-    out.insert("you".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "are".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "have".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "will".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "would".to_string()
-        ]},
-    ]);
-    out.insert("we".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "are".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "have".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "will".to_string()
-        ]},
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "would".to_string()
-        ]},
-    ]);
-    out.insert("will".to_string(), vec![
-        ActionTrigger{action: Action::Contraction, trigger: vec![
-            "not".to_string()
-        ]},
-    ]);
-
-    out.insert("amazing".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("beautiful".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("bad".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("excellent".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("fantastic".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("good".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("great".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("lovely".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-    out.insert("wonderful".to_string(), vec![ActionTrigger{action: Action::SubjectiveAdjective, trigger: vec![]}]);
-
-    out
-}
-
-struct ActionTriggerWithHistory {
-    action_trigger: ActionTrigger,
-    history: Vec<String>
-}
-
-fn process_action(
-    action: Action,
-    sentence: &Vec<String>,
-    history: &Vec<String>,
-    chapter_title: &String, 
-    chapter_number: i32, 
-    section_number: i32, 
-    paragraph_number: i32,
-    number_of_chapters: i32
-) {
-    println!();
-    match action{
-        Action::Filtering => {
-            println!("{}, possible filtering ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::Beginning => {
-            println!("{}, beginning ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::WeakImmediacy=> {
-            println!("{}, weak immediacy ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::PotentialAdverb => {
-            println!("{}, potential adverb ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::Adverb => {
-            println!("{}, adverb ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::Contraction => {
-            println!("{}, missed contraction ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-        Action::SubjectiveAdjective => {
-            println!("{}, subjective adjective ({:?})\n{:?}", loc_string(
-                    &chapter_title, chapter_number, section_number, paragraph_number,
-                    number_of_chapters
-                ), 
-                sentence_string(history), sentence_string(sentence));
-        }
-    }
-    
-}
-
-fn skip_action_in_direct_speech(action: Action) -> bool {
-    action != Action::Contraction
-}
-
-fn add_history_to_state(
-    word: &String, 
-    state: &mut HashMap<String, Vec<ActionTriggerWithHistory>>, 
-){
-    for state_elem in state {
-        for a_t_w_h in state_elem.1 {
-            a_t_w_h.history.push(word.to_string());
-        }
-    }
-}
-
-fn process_sentence(
-    sentence: &Vec<String>, 
-    database: &HashMap<String, Vec<ActionTrigger>>, 
-    dictionary: &HashMap<String, DictionaryElem>,
-    state: &mut HashMap<String, Vec<ActionTriggerWithHistory>>, 
-    chapter_title: &String, 
-    chapter_number: i32, 
-    section_number: i32, 
-    paragraph_number: i32, 
-    document: &Document,
-    direct_speech: bool
-) {
-    for word in sentence {
-        if word.trim() == "" {
-            add_history_to_state(word, state);
+#[derive(ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Only run these rules, separated by commas
+    #[arg(long, value_delimiter = ',', value_parser = parse_rule)]
+    rules: Vec<Action>,
+    /// Don't run these rules, separated by commas
+    #[arg(long, value_delimiter = ',', value_parser = parse_rule)]
+    skip: Vec<Action>,
+    /// Exit with status 1 if there are more findings than this
+    #[arg(long)]
+    max_findings: Option<usize>,
+    /// Keep watching the files, and check them again whenever they change,
+    /// showing what's new and what's been fixed
+    #[arg(long, conflicts_with_all = ["format", "max_findings"])]
+    watch: bool,
+    /// Only report findings that aren't in this baseline file
+    #[arg(long, conflicts_with = "watch")]
+    baseline: Option<PathBuf>,
+    /// Record the findings in a baseline file, instead of reporting them
+    #[arg(long, conflicts_with_all = ["watch", "baseline"])]
+    write_baseline: Option<PathBuf>,
+    /// Rewrite the files, making the fixes suggested for the findings
+    #[arg(long, conflicts_with_all = ["watch", "write_baseline", "format"])]
+    apply: bool,
+    /// Ask before making each fix
+    #[arg(long, requires = "apply")]
+    interactive: bool,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Args)]
+struct ChartsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// The directory to write the charts to
+    #[arg(long, default_value = ".")]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+struct WordsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// How many words and phrases to list
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+}
+
+#[derive(Args)]
+struct ReviewArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// The baseline file ignored findings are added to
+    #[arg(long, default_value = "teastain-baseline.json")]
+    baseline: PathBuf,
+    /// The file to keep how far the review has got in, between sessions
+    #[arg(long, default_value = "teastain-review.json")]
+    progress: PathBuf,
+}
+
+#[derive(Serialize)]
+struct FileReport<'a> {
+    file: String,
+    diagnostics: &'a [Diagnostic],
+}
+
+#[derive(Serialize)]
+struct WordsReport {
+    file: String,
+    report: FrequencyReport,
+}
+
+#[derive(Serialize)]
+struct StatsReport {
+    file: String,
+    stats: Stats,
+    /// The chapters outside the configured reading ages.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    outside_reading_age: Vec<i32>,
+}
+
+fn parse_rule(name: &str) -> Result<Action, String> {
+    Action::from_name(name).ok_or_else(|| format!("unknown rule '{}': see 'teastain rules list'", name))
+}
+
+fn parse_quotes(name: &str) -> Result<QuoteConvention, String> {
+    QuoteConvention::from_name(name).ok_or_else(|| String::from("use uk, us, straight or guillemets"))
+}
+
+fn load_config(settings: &SettingsArgs) -> Result<Config, String> {
+    let mut config = match &settings.config {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        None => Config::default(),
+    };
+    if let Some(quote_convention) = settings.quotes {
+        config.quote_convention = quote_convention;
+    }
+    Ok(config)
+}
+
+//shells expand globs themselves, but not on every platform, and not when
+//they're quoted
+fn expand_files(patterns: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut out = vec![];
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            out.push(PathBuf::from(pattern));
             continue;
         }
+        let paths = glob::glob(pattern).map_err(|e| format!("bad pattern '{}': {}", pattern, e))?;
+        let before = out.len();
+        for path in paths {
+            out.push(path.map_err(|e| e.to_string())?);
+        }
+        if out.len() == before {
+            return Err(format!("no files match '{}'", pattern));
+        }
+    }
+    Ok(out)
+}
+
+fn read_manifest(path: &Path, config: &Config) -> Result<Document, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let manifest: Manifest = toml::from_str(&text).map_err(|e| e.to_string())?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    book::parse_files(&manifest.paths(dir), config).map_err(|e| e.to_string())
+}
+
+fn read_book_dir(dir: &Path, config: &Config) -> Result<Document, String> {
+    let manifest = dir.join(MANIFEST_NAME);
+    if manifest.is_file() {
+        return read_manifest(&manifest, config);
+    }
+    let paths = book::chapter_files(dir).map_err(|e| e.to_string())?;
+    if paths.is_empty() {
+        return Err(String::from("no manuscripts in directory"));
+    }
+    book::parse_files(&paths, config).map_err(|e| e.to_string())
+}
+
+fn read_input(path: &Path, config: &Config) -> Result<Document, String> {
+    if path.is_dir() {
+        read_book_dir(path, config)
+    } else if path.extension().is_some_and(|e| e == "toml") {
+        read_manifest(path, config)
+    } else {
+        parse(path, config).map_err(|e| e.to_string())
+    }
+}
 
-        let lowercase_word = word.to_lowercase();
-
-        let database_entry = database.get(word);
-        match database_entry {
-            Some(action_triggers) => {
-                for action_trigger in action_triggers {
-                    if direct_speech && skip_action_in_direct_speech(action_trigger.action) {
-                        continue;
-                    }
-                    let trigger = &action_trigger.trigger;
-                    if state.contains_key(&lowercase_word) {
-                        let v = state.get_mut(&lowercase_word).unwrap();
-                        v.push(ActionTriggerWithHistory{
-                            action_trigger: ActionTrigger{ 
-                                action: action_trigger.action, 
-                                trigger: trigger.to_vec()
-                            },
-                            history : vec![]
-                        })
-                    } else {
-                        state.insert(lowercase_word.to_string(), vec![
-                            ActionTriggerWithHistory{
-                                action_trigger: ActionTrigger{ 
-                                    action: action_trigger.action, 
-                                    trigger: trigger.to_vec()
-                                },
-                            history : vec![]
-                        }]);
-                    }
+//a document we were asked for, or why we couldn't read it
+type ReadResult = (PathBuf, Result<Document, String>);
+
+fn read_all(input: &InputArgs, config: &Config) -> Result<Vec<ReadResult>, String> {
+    let paths = expand_files(&input.files)?;
+    let results: Vec<Result<Document, String>> = paths.par_iter()
+        .map(|path| read_input(path, config))
+        .collect();
+    Ok(paths.into_iter().zip(results).collect())
+}
+
+//the documents we could read, and whether there were any we couldn't
+fn read_documents(input: &InputArgs, config: &Config) -> Result<(Vec<(PathBuf, Document)>, bool), String> {
+    let mut documents = vec![];
+    let mut failed = false;
+    for (path, result) in read_all(input, config)? {
+        match result {
+            Ok(document) => {
+                for warning in &document.warnings {
+                    eprintln!("warning: {}: {}", path.display(), warning);
                 }
+                documents.push((path, document));
+            }
+            Err(e) => {
+                eprintln!("error: {}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+    Ok((documents, failed))
+}
+
+fn print_text(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        outln!();
+        outln!("{}", diagnostic);
+    }
+}
+
+fn read_baseline(path: &Path) -> Result<Baseline, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write_baseline(path: &Path, baseline: &Baseline) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(baseline).map_err(|e| e.to_string())?;
+    fs::write(path, json + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+enum Answer {
+    Yes,
+    No,
+    Quit,
+}
+
+fn ask(diagnostic: &Diagnostic, fix: &Fix) -> Result<Answer, String> {
+    outln!();
+    outln!("{}", diagnostic);
+    loop {
+        out!("Replace {:?} with {:?}? [y/n/q] ", fix.original, fix.replacement);
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        //the end of input means there's no one to ask
+        if io::stdin().read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Ok(Answer::Quit);
+        }
+        match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(Answer::Yes),
+            "n" | "no" => return Ok(Answer::No),
+            "q" | "quit" => return Ok(Answer::Quit),
+            _ => (),
+        }
+    }
+}
+
+//make the suggested fixes, file by file, returning whether any file
+//couldn't be rewritten
+fn apply_fixes(diagnostics: &[Diagnostic], interactive: bool) -> Result<bool, String> {
+    //a book's findings come from several files
+    let mut by_file: Vec<(&str, Vec<Fix>)> = vec![];
+    for diagnostic in diagnostics {
+        let Some(fix) = Fix::of(diagnostic) else {
+            continue;
+        };
+        if interactive {
+            match ask(diagnostic, &fix)? {
+                Answer::Yes => (),
+                Answer::No => continue,
+                Answer::Quit => break,
+            }
+        }
+        let file = diagnostic.location.file.as_str();
+        match by_file.iter_mut().find(|(f, _)| *f == file) {
+            Some((_, fixes)) => fixes.push(fix),
+            None => by_file.push((file, vec![fix])),
+        }
+    }
+
+    let mut failed = false;
+    for (file, fixes) in by_file {
+        match fixes::apply(Path::new(file), &fixes) {
+            Ok(made) => outln!("{}: made {} of {} fixes", file, made, fixes.len()),
+            Err(e) => {
+                eprintln!("error: {}: {}", file, e);
+                failed = true;
+            }
+        }
+    }
+    Ok(failed)
+}
+
+//not being able to read a file matters more than having too many findings
+fn check_exit_code(failed: bool, findings: usize, max_findings: Option<usize>) -> ExitCode {
+    if failed {
+        ExitCode::from(EXIT_ERROR)
+    } else if max_findings.is_some_and(|max| findings > max) {
+        ExitCode::from(EXIT_FINDINGS)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn check(args: &CheckArgs) -> Result<ExitCode, String> {
+    let config = load_config(&args.input.settings)?;
+    let (documents, failed) = read_documents(&args.input, &config)?;
+
+    let rules: Vec<Action> = Action::ALL.into_iter()
+        .filter(|rule| args.rules.is_empty() || args.rules.contains(rule))
+        .filter(|rule| !args.skip.contains(rule))
+        .collect();
+    let checker = Checker::with_rules(config, rules);
+
+    let mut results: Vec<(&PathBuf, Vec<Diagnostic>)> = documents.par_iter()
+        .map(|(path, document)| (path, checker.check(document)))
+        .collect();
+
+    if let Some(path) = &args.write_baseline {
+        let diagnostics: Vec<Diagnostic> = results.into_iter().flat_map(|(_, diagnostics)| diagnostics).collect();
+        write_baseline(path, &Baseline::new(&diagnostics))?;
+        eprintln!("Recorded {} findings in {}", diagnostics.len(), path.display());
+        return Ok(exit_code(failed));
+    }
+    if let Some(path) = &args.baseline {
+        let baseline = read_baseline(path)?;
+        results = results.into_iter()
+            .map(|(path, diagnostics)| (path, baseline.new_findings(diagnostics)))
+            .collect();
+    }
+    let findings: usize = results.iter().map(|(_, diagnostics)| diagnostics.len()).sum();
+
+    if args.apply {
+        let diagnostics: Vec<Diagnostic> = results.into_iter().flat_map(|(_, diagnostics)| diagnostics).collect();
+        let apply_failed = apply_fixes(&diagnostics, args.interactive)?;
+        return Ok(exit_code(failed || apply_failed));
+    }
+    if args.watch {
+        for (_, diagnostics) in &results {
+            print_text(diagnostics);
+        }
+        let diagnostics = results.into_iter().flat_map(|(_, diagnostics)| diagnostics).collect();
+        watch(&args.input, &checker, diagnostics);
+    }
+
+    match args.format {
+        Format::Text => {
+            for (_, diagnostics) in &results {
+                print_text(diagnostics);
+            }
+        }
+        Format::Json => {
+            let reports: Vec<FileReport> = results.iter()
+                .map(|(path, diagnostics)| FileReport { file: path.display().to_string(), diagnostics })
+                .collect();
+            let json = serde_json::to_string_pretty(&reports).map_err(|e| e.to_string())?;
+            outln!("{}", json);
+        }
+    }
+
+    Ok(check_exit_code(failed, findings, args.max_findings))
+}
+
+//the files to look at for changes. For a book, that's everything in its
+//directory, so that we see chapters being added
+fn watched_files(input: &InputArgs) -> Result<Vec<PathBuf>, String> {
+    let mut out = vec![];
+    for path in expand_files(&input.files)? {
+        let dir = if path.is_dir() {
+            path
+        } else if path.extension().is_some_and(|e| e == "toml") {
+            path.parent().map_or(PathBuf::from("."), Path::to_path_buf)
+        } else {
+            out.push(path);
+            continue;
+        };
+        let entries = fs::read_dir(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        out.extend(entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()));
+    }
+    out.sort();
+    Ok(out)
+}
+
+fn modification_times(input: &InputArgs) -> Result<Vec<(PathBuf, Option<SystemTime>)>, String> {
+    let files = watched_files(input)?;
+    Ok(files.into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect())
+}
+
+fn print_diff(diff: &FindingsDiff) {
+    print_text(&diff.new);
+    outln!();
+    outln!("{:<22}{:>6}{:>10}{:>11}", "", "new", "resolved", "unchanged");
+    for (action, counts) in diff.counts() {
+        outln!("{:<22}{:>6}{:>10}{:>11}", action.name(), counts.new, counts.resolved, counts.unchanged);
+    }
+}
+
+//check again whenever the files change, until we're interrupted
+fn watch(input: &InputArgs, checker: &Checker, mut previous: Vec<Diagnostic>) -> ! {
+    let mut times = modification_times(input).ok();
+    outln!();
+    outln!("Watching for changes...");
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let now = modification_times(input).ok();
+        if now == times {
+            continue;
+        }
+        times = now;
+
+        //a file caught half way through being saved can't be read; we'll
+        //try again when it's finished
+        let documents = match read_documents(input, checker.config()) {
+            Ok((documents, false)) => documents,
+            Ok((_, true)) => continue,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                continue;
             }
-            None => ()
-        }
-
-        //state check
-        if let Some(state_entry) = state.get(&lowercase_word) {
-            let mut new_state: HashMap<String, Vec<ActionTriggerWithHistory>> = HashMap::new();
-            for a_t_w_h in state_entry {
-                let mut history = a_t_w_h.history.clone();
-                history.push(word.to_string());
-                if a_t_w_h.action_trigger.trigger.len() == 0 {    
-                    process_action(a_t_w_h.action_trigger.action, sentence, &history,
-                        chapter_title, chapter_number, section_number, paragraph_number, document.number_of_chapters);
-                } else {
-                    let bits = a_t_w_h.action_trigger.trigger.split_first().unwrap();
-                    if new_state.contains_key(bits.0) {
-                        let v: &mut Vec<ActionTriggerWithHistory> = new_state.get_mut(bits.0).unwrap();
-                        v.push(ActionTriggerWithHistory{
-                            action_trigger: ActionTrigger{ 
-                                action: a_t_w_h.action_trigger.action, 
-                                trigger: bits.1.to_vec()
-                            },
-                            history: history
-                        })
-                    } else {
-                        new_state.insert(bits.0.to_string(), vec![
-                            ActionTriggerWithHistory{
-                                action_trigger: ActionTrigger{ 
-                                    action: a_t_w_h.action_trigger.action, 
-                                    trigger: bits.1.to_vec()
-                                },
-                                history: history
-                        }]);
-                    }
+        };
+        let current: Vec<Diagnostic> = documents.par_iter()
+            .map(|(_, document)| checker.check(document))
+            .collect::<Vec<Vec<Diagnostic>>>()
+            .concat();
+
+        outln!();
+        outln!("Changed, checking again:");
+        print_diff(&FindingsDiff::between(&previous, &current));
+        previous = current;
+    }
+}
+
+fn print_counts_header() {
+    outln!("  {:<28}{:>10}{:>10}{:>11}{:>8}{:>10}{:>13}", "", "words", "sentences", "paragraphs", "speech", "findings", "per 1k words");
+}
+
+fn print_counts(label: &str, counts: &Counts) {
+    outln!("  {:<28}{:>10}{:>10}{:>11}{:>7.0}%{:>10}{:>13.1}",
+        label, counts.words, counts.sentences, counts.paragraphs, counts.speech_ratio * 100.0,
+        counts.findings, counts.findings_per_thousand_words);
+}
+
+fn print_readability_header() {
+    outln!("  {:<28}{:>13}{:>11}{:>8}{:>8}{:>14}{:>13}", "", "Flesch ease", "F-K grade", "Fog", "SMOG", "Coleman-Liau", "reading age");
+}
+
+fn print_readability(label: &str, readability: &Readability, band: Option<&ReadingAgeBand>) {
+    let outside = band.is_some_and(|band| !band.contains(readability.reading_age));
+    outln!("  {:<28}{:>13.1}{:>11.1}{:>8.1}{:>8.1}{:>14.1}{:>13.1}{}",
+        label, readability.flesch_reading_ease, readability.flesch_kincaid_grade, readability.gunning_fog,
+        readability.smog, readability.coleman_liau, readability.reading_age, if outside { "  !" } else { "" });
+}
+
+fn chapter_label(chapter: &ChapterStats) -> String {
+    let label = if chapter.title.is_empty() {
+        format!("Chapter {}", chapter.number)
+    } else {
+        format!("{}. {}", chapter.number, chapter.title)
+    };
+    label.chars().take(28).collect()
+}
+
+fn print_stats(path: &Path, stats: &Stats, band: Option<&ReadingAgeBand>) {
+    let lengths = &stats.sentence_lengths;
+    let balance = &stats.chapter_balance;
+    outln!("{}", path.display());
+    outln!("  Chapters:          {}", stats.chapters);
+    outln!("  Sections:          {}", stats.sections);
+    outln!("  Paragraphs:        {}", stats.totals.paragraphs);
+    outln!("  Sentences:         {}", stats.totals.sentences);
+    outln!("  Speech sentences:  {}", stats.totals.speech_sentences);
+    outln!("  Words:             {}", stats.totals.words);
+    outln!("  Speech:            {:.0}% of words", stats.totals.speech_ratio * 100.0);
+    outln!("  Findings:          {} ({:.1} per 1,000 words)", stats.totals.findings, stats.totals.findings_per_thousand_words);
+    outln!();
+    outln!("  Sentence length:   average {:.1}, median {}, shortest {}, longest {}",
+        lengths.average, lengths.median, lengths.shortest, lengths.longest);
+    let most = lengths.distribution.iter().map(|b| b.sentences).max().unwrap_or(0).max(1);
+    for bucket in &lengths.distribution {
+        let label = match bucket.to {
+            Some(to) => format!("{}-{} words", bucket.from, to - 1),
+            None => format!("{}+ words", bucket.from),
+        };
+        outln!("    {:<14}{:>6}  {}", label, bucket.sentences, "#".repeat(bucket.sentences * 40 / most));
+    }
+    outln!();
+    outln!("  Chapter length:    average {:.0} words, standard deviation {:.0}, shortest chapter {}, longest chapter {}",
+        balance.mean_words, balance.standard_deviation, balance.shortest, balance.longest);
+    outln!();
+    print_counts_header();
+    for chapter in &stats.chapter_stats {
+        print_counts(&chapter_label(chapter), &chapter.counts);
+        if chapter.sections.len() > 1 {
+            for section in &chapter.sections {
+                print_counts(&format!("  Section {}", section.number), &section.counts);
+            }
+        }
+    }
+    print_counts("Total", &stats.totals);
+
+    outln!();
+    print_readability_header();
+    for chapter in &stats.chapter_stats {
+        print_readability(&chapter_label(chapter), &chapter.counts.readability, band);
+        if chapter.sections.len() > 1 {
+            for section in &chapter.sections {
+                print_readability(&format!("  Section {}", section.number), &section.counts.readability, None);
+            }
+        }
+    }
+    print_readability("Total", &stats.totals.readability, None);
+
+    if let Some(band) = band {
+        let outside = stats.chapters_outside(band);
+        if !outside.is_empty() {
+            let numbers: Vec<String> = outside.iter().map(|c| c.number.to_string()).collect();
+            outln!();
+            outln!("  Chapters outside reading ages {} to {}: {}", band.min, band.max, numbers.join(", "));
+        }
+    }
+}
+
+fn stats(args: &StatsArgs) -> Result<ExitCode, String> {
+    let config = load_config(&args.input.settings)?;
+    let (documents, failed) = read_documents(&args.input, &config)?;
+    let band = config.reading_age;
+    let checker = Checker::new(config);
+    let results: Vec<(&PathBuf, Stats)> = documents.par_iter()
+        .map(|(path, document)| (path, Stats::of(document, &checker.check(document))))
+        .collect();
+
+    match args.format {
+        Format::Text => {
+            for (i, (path, stats)) in results.iter().enumerate() {
+                if i > 0 {
+                    outln!();
                 }
+                print_stats(path, stats, band.as_ref());
             }
-            *state = new_state;
+        }
+        Format::Json => {
+            let reports: Vec<StatsReport> = results.into_iter()
+                .map(|(path, stats)| {
+                    let outside_reading_age = band.map_or(vec![], |band| {
+                        stats.chapters_outside(&band).iter().map(|c| c.number).collect()
+                    });
+                    StatsReport { file: path.display().to_string(), stats, outside_reading_age }
+                })
+                .collect();
+            let json = serde_json::to_string_pretty(&reports).map_err(|e| e.to_string())?;
+            outln!("{}", json);
+        }
+    }
+    Ok(exit_code(failed))
+}
+
+fn charts(args: &ChartsArgs) -> Result<ExitCode, String> {
+    let config = load_config(&args.input.settings)?;
+    let (documents, failed) = read_documents(&args.input, &config)?;
+    let checker = Checker::new(config);
+    fs::create_dir_all(&args.out).map_err(|e| format!("{}: {}", args.out.display(), e))?;
+    for (path, document) in &documents {
+        let stats = Stats::of(document, &checker.check(document));
+        //a book's directory has no stem of its own worth using
+        let stem = path.file_stem().map_or(String::from("book"), |s| s.to_string_lossy().into_owned());
+        for chart in charts::pacing_charts(document, &stats) {
+            let chart_path = args.out.join(format!("{}-{}.svg", stem, chart.name));
+            fs::write(&chart_path, chart.svg).map_err(|e| format!("{}: {}", chart_path.display(), e))?;
+            outln!("{}", chart_path.display());
+        }
+    }
+    Ok(exit_code(failed))
+}
+
+fn print_word_counts(word_counts: &[WordCount], indent: &str) {
+    for w in word_counts {
+        let times_normal = w.times_normal.map_or(String::from(""), |times| format!("{:.1}x normal", times));
+        outln!("{}{:<20}{:>6}{:>10.1} per 10k  {}", indent, w.word, w.count, w.per_10k_words, times_normal);
+    }
+}
+
+fn print_words(path: &Path, report: &FrequencyReport) {
+    outln!("{}", path.display());
+    outln!("  Most used words, of {}:", report.words);
+    print_word_counts(&report.top_words, "    ");
+    for (i, ngrams) in report.ngrams.iter().enumerate() {
+        if ngrams.is_empty() {
+            continue;
+        }
+        outln!();
+        outln!("  Most used runs of {} words:", i + 2);
+        for ngram in ngrams {
+            outln!("    {:<36}{:>6}", ngram.ngram, ngram.count);
+        }
+    }
+    if !report.overused.is_empty() {
+        outln!();
+        outln!("  Overused:");
+        print_word_counts(&report.overused, "    ");
+    }
+    for chapter in &report.chapters {
+        outln!();
+        if chapter.title.is_empty() {
+            outln!("  Chapter {}, {} words", chapter.number, chapter.words);
         } else {
-            state.clear();
+            outln!("  Chapter {}: {}, {} words", chapter.number, chapter.title, chapter.words);
         }
+        print_word_counts(&chapter.top_words, "    ");
+        if !chapter.overused.is_empty() {
+            outln!("    Overused:");
+            print_word_counts(&chapter.overused, "      ");
+        }
+    }
+}
 
-        if !direct_speech && word.ends_with("ly") {
-            let o_dictionary_elem = dictionary.get(word);
-            match o_dictionary_elem {
-                Some(dictionary_elem) => {
-                    if dictionary_elem.part_of_speech == PartOfSpeech::Adverb {
-                        process_action(Action::Adverb, sentence, &vec![word.to_string()],
-                    chapter_title, chapter_number, section_number, paragraph_number, document.number_of_chapters);
-                    }
+fn words(args: &WordsArgs) -> Result<ExitCode, String> {
+    let config = load_config(&args.input.settings)?;
+    let (documents, failed) = read_documents(&args.input, &config)?;
+    let results: Vec<(&PathBuf, FrequencyReport)> = documents.par_iter()
+        .map(|(path, document)| (path, FrequencyReport::of(document, &config, args.top)))
+        .collect();
+
+    match args.format {
+        Format::Text => {
+            for (i, (path, report)) in results.iter().enumerate() {
+                if i > 0 {
+                    outln!();
                 }
-                None => process_action(Action::PotentialAdverb, sentence, &vec![word.to_string()],
-                    chapter_title, chapter_number, section_number, paragraph_number, document.number_of_chapters)
+                print_words(path, report);
             }
-            
+        }
+        Format::Json => {
+            let reports: Vec<WordsReport> = results.into_iter()
+                .map(|(path, report)| WordsReport { file: path.display().to_string(), report })
+                .collect();
+            let json = serde_json::to_string_pretty(&reports).map_err(|e| e.to_string())?;
+            outln!("{}", json);
         }
     }
+    Ok(exit_code(failed))
+}
+
+fn export_text(input: &InputArgs) -> Result<ExitCode, String> {
+    let config = load_config(&input.settings)?;
+    let (documents, failed) = read_documents(input, &config)?;
+    let texts: Vec<String> = documents.iter().map(|(_, document)| document.text()).collect();
+    out!("{}", texts.join("\n"));
+    Ok(exit_code(failed))
 }
 
-fn score(document: &Document) {
-    let mut chapter_number= 1;
-    let mut section_number = 1;
-    let mut paragraph_number = 1;
-    let mut chapter_title = String::from("");
-    let database = init_database();
-    let dictionary = init_dictionary();
+//the review UI's view of the manuscripts, the baseline and the progress
+//file
+struct ReviewSession<'a> {
+    args: &'a ReviewArgs,
+    checker: Checker,
+    baseline: Baseline,
+    progress: ReviewProgress,
+}
+
+impl Review for ReviewSession<'_> {
+    fn findings(&mut self) -> Result<Vec<Finding>, String> {
+        let mut out = vec![];
+        //warnings were shown before the review started
+        for (path, result) in read_all(&self.args.input, self.checker.config())? {
+            let document = result.map_err(|e| format!("{}: {}", path.display(), e))?;
+            let diagnostics = self.baseline.new_findings(self.checker.check(&document));
+            out.extend(review::findings(&document, diagnostics));
+        }
+        Ok(out)
+    }
+
+    fn progress(&mut self) -> &mut ReviewProgress {
+        &mut self.progress
+    }
 
-    let elements = &document.elements;
-    let mut state: HashMap<String, Vec<ActionTriggerWithHistory>> = HashMap::new();
-    for element in elements {
-        match element {
-            DocumentElement::ChapterEnd => {
-                chapter_number += 1;
-                section_number = 1;
-                paragraph_number = 1;
+    fn act(&mut self, diagnostic: &Diagnostic, status: ReviewStatus) -> Result<(), String> {
+        match status {
+            ReviewStatus::Accepted => (),
+            ReviewStatus::Ignored => {
+                self.baseline.findings.extend(Baseline::new(std::slice::from_ref(diagnostic)).findings);
+                write_baseline(&self.args.baseline, &self.baseline)?;
             }
-            DocumentElement::SectionEnd => {
-                section_number += 1;
-                paragraph_number = 1;
+            ReviewStatus::Fixed => {
+                let fix = Fix::of(diagnostic).ok_or("no fix for this finding")?;
+                let path = Path::new(&diagnostic.location.file);
+                if fixes::apply(path, &[fix]).map_err(|e| e.to_string())? == 0 {
+                    return Err(String::from("the text has changed since it was checked"));
+                }
             }
-            DocumentElement::ParagraphEnd => {
-                paragraph_number += 1;
+        }
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.progress).map_err(|e| e.to_string())?;
+        fs::write(&self.args.progress, json + "\n").map_err(|e| format!("{}: {}", self.args.progress.display(), e))
+    }
+}
+
+fn review(args: &ReviewArgs) -> Result<ExitCode, String> {
+    let config = load_config(&args.input.settings)?;
+    //read everything once first, to show the warnings and errors
+    let (_, failed) = read_documents(&args.input, &config)?;
+    if failed {
+        return Ok(ExitCode::from(EXIT_ERROR));
+    }
+    let baseline = if args.baseline.exists() { read_baseline(&args.baseline)? } else { Baseline::default() };
+    let progress = if args.progress.exists() {
+        let text = fs::read_to_string(&args.progress).map_err(|e| format!("{}: {}", args.progress.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", args.progress.display(), e))?
+    } else {
+        ReviewProgress::default()
+    };
+
+    let mut session = ReviewSession { args, checker: Checker::new(config), baseline, progress };
+    review::run(&mut session).map_err(|e| e.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn lsp(settings: &SettingsArgs) -> Result<ExitCode, String> {
+    let config = load_config(settings)?;
+    teastain::lsp::run(Checker::new(config)).map_err(|e| e.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn rules(command: &RulesCommand) -> ExitCode {
+    match command {
+        RulesCommand::List => {
+            for action in Action::ALL {
+                outln!("{:<22}{}", action.name(), action.description());
             }
-            DocumentElement::ChapterName(_) => (),
-            DocumentElement::ChapterTitle(this_chapter_title) => {
-                chapter_title = this_chapter_title.clone();
-            },
-            DocumentElement::DirectSpeech(sentence) => 
-                process_sentence(sentence, &database, &dictionary, &mut state, &chapter_title, chapter_number, section_number, paragraph_number, document, true),
-            DocumentElement::Sentence(sentence) =>
-                process_sentence(sentence, &database, &dictionary, &mut state, &chapter_title, chapter_number, section_number, paragraph_number, document, false),
+        }
+        RulesCommand::Explain { rule } => {
+            outln!("{} ({})", rule.name(), rule.description());
+            outln!();
+            outln!("{}", rule.explanation());
         }
     }
+    ExitCode::SUCCESS
 }
 
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Check(args) => check(args),
+        Command::Stats(args) => stats(args),
+        Command::ExportText(input) => export_text(input),
+        Command::Charts(args) => charts(args),
+        Command::Words(args) => words(args),
+        Command::Review(args) => review(args),
+        Command::Lsp(settings) => lsp(settings),
+        Command::Rules { command } => Ok(rules(command)),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        println!("Must supply a file");
-        return;
-    } else {
-        let file_name = &args[1];
-        let path = Path::new(file_name);
-        let document = parse(&path);
-        score(&document);
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("teastain-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(check_exit_code(false, 0, None), ExitCode::SUCCESS);
+        assert_eq!(check_exit_code(false, 100, None), ExitCode::SUCCESS);
+        assert_eq!(check_exit_code(false, 3, Some(3)), ExitCode::SUCCESS);
+        assert_eq!(check_exit_code(false, 4, Some(3)), ExitCode::from(EXIT_FINDINGS));
+        assert_eq!(check_exit_code(false, 1, Some(0)), ExitCode::from(EXIT_FINDINGS));
+        assert_eq!(check_exit_code(true, 0, Some(3)), ExitCode::from(EXIT_ERROR));
+        assert_eq!(check_exit_code(true, 4, Some(3)), ExitCode::from(EXIT_ERROR));
+        assert_eq!(exit_code(false), ExitCode::SUCCESS);
+        assert_eq!(exit_code(true), ExitCode::from(EXIT_ERROR));
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let path = temp_path("baseline");
+        let diagnostics = Checker::with_rules(Config::default(), vec![Action::WeakImmediacy])
+            .check(&teastain::markdown::parse_str("She was there just then.\n", "test.md", &Config::default()));
+        let baseline = Baseline::new(&diagnostics);
+        write_baseline(&path, &baseline).unwrap();
+        let read = read_baseline(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read.findings.len(), 1);
+        assert_eq!(read.findings[0].fingerprint, baseline.findings[0].fingerprint);
+        assert!(read.new_findings(diagnostics).is_empty());
+    }
+
+    #[test]
+    fn missing_baseline() {
+        let path = temp_path("missing-baseline");
+        let error = read_baseline(&path).unwrap_err();
+        assert!(error.starts_with(&path.display().to_string()), "{}", error);
+    }
+
+    #[test]
+    fn corrupt_baseline() {
+        let path = temp_path("corrupt-baseline");
+        fs::write(&path, "{\"findings\": [{\"rule\": \"no-such-rule\"").unwrap();
+        let error = read_baseline(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(error.unwrap_err().starts_with(&path.display().to_string()));
     }
 }
@@ -0,0 +1,266 @@
+use std::{fs, path::Path};
+
+use crate::builder::{paragraph_is_section_break, DocumentBuilder};
+use crate::config::Config;
+use crate::document::{Document, Paragraph};
+use crate::error::TeastainError;
+use crate::fixes::{self, Fix};
+use crate::suppress::Suppression;
+
+//'***', '---' or '___', with or without spaces
+fn is_thematic_break(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && ['*', '-', '_'].iter().any(|mark| marks.iter().all(|c| c == mark))
+}
+
+//the heading's level and text, for '# Chapter One'
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = &line[level..];
+    if level > 0 && text.starts_with(' ') {
+        Some((level, text.trim()))
+    } else {
+        None
+    }
+}
+
+//the line as prose. Markup is blanked out rather than removed, so that
+//offsets in the paragraph are still offsets in the file
+fn prose_line(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut at_start = true;
+    for (i, c) in chars.iter().enumerate() {
+        let is_markup = match c {
+            '\n' | '\r' | '*' => true,
+            //a block quote, or a list item
+            '>' | '-' | '+' => at_start && chars.get(i + 1).is_none_or(|next| next.is_whitespace()),
+            //emphasis, but not an underscore inside a word
+            '_' => {
+                let previous = if i > 0 { chars.get(i - 1) } else { None };
+                !(previous.is_some_and(|p| p.is_alphanumeric()) && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric()))
+            }
+            _ => false,
+        };
+        if !c.is_whitespace() && !is_markup {
+            at_start = false;
+        }
+        if is_markup {
+            out.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+//take the HTML comments out of a line, blanking them like other markup
+fn take_comments(line: &str, comments: &mut Vec<String>) -> String {
+    let mut out = String::from(line);
+    let mut from = 0;
+    while let Some(start) = out[from..].find("<!--").map(|i| from + i) {
+        let Some(end) = out[start..].find("-->").map(|i| start + i + 3) else {
+            break;
+        };
+        comments.push(out[start..end].to_string());
+        out.replace_range(start..end, &" ".repeat(end - start));
+        from = end;
+    }
+    out
+}
+
+struct MarkdownReader<'a> {
+    builder: DocumentBuilder<'a>,
+    file: String,
+    //every block counts, headings and breaks included, as every text:p does
+    //in ODT
+    paragraph_count: usize,
+    //the paragraph we're in the middle of: its first line, offset and text
+    paragraph: Option<(usize, usize, String)>,
+    //whether there's been anything yet, so a heading starts a new chapter
+    started: bool,
+    warnings: Vec<TeastainError>,
+}
+
+impl<'a> MarkdownReader<'a> {
+    fn flush(&mut self) {
+        if let Some((line_number, offset, text)) = self.paragraph.take() {
+            self.builder.paragraph(Paragraph {
+                index: self.paragraph_count,
+                text: text.trim_end().to_string(),
+                file: self.file.clone(),
+                source_path: format!("line {}", line_number),
+                offset,
+                suppressions: vec![],
+            });
+            self.paragraph_count += 1;
+            self.started = true;
+        }
+    }
+
+    //a comment suppresses rules in the paragraph it's in, or the one after
+    fn comment(&mut self, comment: &str) {
+        match Suppression::parse(comment) {
+            Some(Ok(suppression)) => self.builder.suppress(suppression),
+            Some(Err(word)) => self.warnings.push(TeastainError::InvalidSuppression(word)),
+            None => (),
+        }
+    }
+
+    fn block(&mut self) {
+        self.paragraph_count += 1;
+        self.started = true;
+    }
+}
+
+/// Read a Markdown or plain text manuscript from a string. `file` is what
+/// findings give as the file they're in. A '#' heading starts a chapter,
+/// with the heading as its title; a '##' heading names it. '* * *' and
+/// Markdown's thematic breaks end sections. A '<!-- teastain-ignore -->'
+/// comment suppresses rules in the paragraph it's in or the one after it.
+pub fn parse_str(text: &str, file: &str, config: &Config) -> Document {
+    let mut reader = MarkdownReader {
+        builder: DocumentBuilder::new(config),
+        file: file.to_string(),
+        paragraph_count: 0,
+        paragraph: None,
+        started: false,
+        warnings: vec![],
+    };
+    let mut in_code_block = false;
+
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            reader.flush();
+            in_code_block = !in_code_block;
+        } else if in_code_block {
+            //not prose
+        } else if trimmed.starts_with("<!--") && trimmed.ends_with("-->") && reader.paragraph.is_none() {
+            //a comment in a paragraph is blanked out with the rest of the
+            //line below, so that the offsets after it stay right
+            reader.comment(trimmed);
+        } else if trimmed.is_empty() {
+            reader.flush();
+        } else if paragraph_is_section_break(trimmed) || is_thematic_break(trimmed) {
+            reader.flush();
+            reader.builder.section_end(trimmed);
+            reader.block();
+        } else if let Some((level, title)) = heading(trimmed) {
+            reader.flush();
+            if level == 1 {
+                if reader.started {
+                    reader.builder.chapter_end();
+                }
+                reader.builder.chapter_title(title.to_string());
+            } else {
+                reader.builder.chapter_name(title.to_string());
+            }
+            reader.block();
+        } else {
+            let mut comments = vec![];
+            let line = prose_line(&take_comments(line, &mut comments));
+            for comment in comments {
+                reader.comment(&comment);
+            }
+            match &mut reader.paragraph {
+                Some((_, _, paragraph_text)) => *paragraph_text += &line,
+                None => reader.paragraph = Some((i + 1, line_offset, line)),
+            }
+        }
+    }
+    reader.flush();
+
+    let mut document = reader.builder.finish();
+    document.warnings = reader.warnings;
+    document
+}
+
+/// Read a Markdown or plain text manuscript.
+pub fn parse(path: &Path, config: &Config) -> Result<Document, TeastainError> {
+    let text = fs::read_to_string(path)?;
+    Ok(parse_str(&text, &path.display().to_string(), config))
+}
+
+/// Make fixes to Markdown or plain text, where a paragraph's offset and a
+/// span in it are where the text is in the file. Returns the new text and
+/// how many fixes were made.
+pub fn apply_fixes_str(text: &str, fixes: &[Fix]) -> (String, usize) {
+    let range = |fix: &Fix| fix.offset + fix.span.start..fix.offset + fix.span.end;
+    let usable = fixes::usable(fixes, |fix| text.get(range(fix)));
+
+    let mut out = String::with_capacity(text.len());
+    let mut from = 0;
+    for fix in &usable {
+        let range = range(fix);
+        out += &text[from..range.start];
+        out += &fix.replacement;
+        from = range.end;
+    }
+    out += &text[from..];
+    (out, usable.len())
+}
+
+/// Make fixes to a Markdown or plain text manuscript.
+pub fn apply_fixes(path: &Path, fixes: &[Fix]) -> Result<usize, TeastainError> {
+    let text = fs::read_to_string(path)?;
+    let (text, count) = apply_fixes_str(&text, fixes);
+    if count > 0 {
+        fs::write(path, text)?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{check_markdown, Action, Checker};
+
+    fn contractions(text: &str) -> Vec<Fix> {
+        check_markdown(text, Action::Contraction, Config::default()).iter().filter_map(Fix::of).collect()
+    }
+
+    #[test]
+    fn spans_are_offsets_in_the_file() {
+        let text = "Line one I am here\nand I do not know.\n";
+        let fixes = contractions(text);
+        assert_eq!(fixes.len(), 2);
+        for fix in &fixes {
+            assert_eq!(&text[fix.offset + fix.span.start..fix.offset + fix.span.end], fix.original);
+        }
+        assert_eq!(apply_fixes_str(text, &fixes), (String::from("Line one I’m here\nand I don’t know.\n"), 2));
+    }
+
+    #[test]
+    fn comment_line_inside_a_paragraph() {
+        let text = "Line one I am here\n<!-- a note -->\nand I do not know what I saw.\n";
+        let fixes = contractions(text);
+        assert_eq!(fixes.len(), 2);
+        for fix in &fixes {
+            assert_eq!(&text[fix.offset + fix.span.start..fix.offset + fix.span.end], fix.original);
+        }
+        let (fixed, count) = apply_fixes_str(text, &fixes);
+        assert_eq!(count, 2);
+        assert_eq!(fixed, "Line one I’m here\n<!-- a note -->\nand I don’t know what I saw.\n");
+    }
+
+    #[test]
+    fn suppression_marker_line_inside_a_paragraph() {
+        let text = "Line one I am here\n<!-- teastain-ignore filtering -->\nand I do not know what I saw.\n";
+        let config = Config::default();
+        let document = parse_str(text, "test.md", &config);
+        let diagnostics = Checker::new(config).check(&document);
+        assert!(diagnostics.iter().all(|d| d.action != Action::Filtering));
+
+        let fixes: Vec<Fix> = diagnostics.iter().filter_map(Fix::of).collect();
+        assert_eq!(fixes.len(), 2);
+        for fix in &fixes {
+            assert_eq!(&text[fix.offset + fix.span.start..fix.offset + fix.span.end], fix.original);
+        }
+        assert_eq!(apply_fixes_str(text, &fixes).1, 2);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::checker::Action;
+use crate::database::ActionTrigger;
+use crate::document::Word;
+
+/// A trigger whose first word has been seen, waiting on the rest of its phrase.
+#[derive(Debug)]
+struct PartialMatch {
+    action: Action,
+    remaining: Vec<String>,
+    history: Vec<Word>,
+}
+
+/// A trigger whose whole phrase has been seen.
+#[derive(Debug)]
+pub struct Match {
+    pub action: Action,
+    pub history: Vec<Word>,
+}
+
+/// Runs the trigger database over a stream of words. Every partial match is
+/// tracked on its own, so a word that fails one pending phrase does not
+/// disturb any other, and a word can both finish one phrase and start another.
+#[derive(Debug)]
+#[derive(Default)]
+pub struct Matcher {
+    partials: Vec<PartialMatch>,
+}
+
+impl Matcher {
+    pub fn new() -> Matcher {
+        Matcher { partials: vec![] }
+    }
+
+    /// Feed the next word, returning every trigger it completes.
+    pub fn push_word(
+        &mut self,
+        word: &Word,
+        database: &HashMap<String, Vec<ActionTrigger>>,
+        direct_speech: bool,
+    ) -> Vec<Match> {
+        let mut out = vec![];
+
+        //whitespace can't break a phrase, but we keep it so the reported
+        //text reads naturally
+        if word.text.trim().is_empty() {
+            for partial in &mut self.partials {
+                partial.history.push(word.clone());
+            }
+            return out;
+        }
+
+        let lowercase_word = word.text.to_lowercase();
+
+        let mut partials = vec![];
+        for mut partial in self.partials.drain(..) {
+            if partial.remaining[0] != lowercase_word {
+                continue;
+            }
+            partial.history.push(word.clone());
+            partial.remaining.remove(0);
+            if partial.remaining.is_empty() {
+                out.push(Match { action: partial.action, history: partial.history });
+            } else {
+                partials.push(partial);
+            }
+        }
+
+        if let Some(action_triggers) = database.get(&lowercase_word) {
+            for action_trigger in action_triggers {
+                if direct_speech && crate::checker::skip_action_in_direct_speech(action_trigger.action) {
+                    continue;
+                }
+                let history = vec![word.clone()];
+                if action_trigger.trigger.is_empty() {
+                    out.push(Match { action: action_trigger.action, history });
+                } else {
+                    partials.push(PartialMatch {
+                        action: action_trigger.action,
+                        remaining: action_trigger.trigger.clone(),
+                        history,
+                    });
+                }
+            }
+        }
+
+        self.partials = partials;
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::findings;
+    use crate::database::init_database;
+
+    //the words of a phrase, with the spaces between them as words of their
+    //own, the way the tokenizer gives them
+    fn words(text: &str) -> Vec<Word> {
+        text.split_inclusive(' ')
+            .flat_map(|w| match w.strip_suffix(' ') {
+                Some(word) => vec![Word::synthetic(word), Word::synthetic(" ")],
+                None => vec![Word::synthetic(w)],
+            })
+            .filter(|w| !w.text.is_empty())
+            .collect()
+    }
+
+    fn matches(text: &str) -> Vec<(Action, String)> {
+        let database = init_database();
+        let mut matcher = Matcher::new();
+        words(text).iter()
+            .flat_map(|word| matcher.push_word(word, &database, false))
+            .map(|m| (m.action, m.history.iter().map(|w| w.text.as_str()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn phrase_on_its_own() {
+        assert_eq!(matches("just then"), vec![(Action::WeakImmediacy, String::from("just then"))]);
+    }
+
+    #[test]
+    fn phrase_after_an_unrelated_trigger() {
+        //'i' is waiting on 'am', 'have' and the like when 'just' comes
+        assert_eq!(matches("I just then"), vec![(Action::WeakImmediacy, String::from("just then"))]);
+    }
+
+    #[test]
+    fn triggers_pending_on_different_words() {
+        //'i' starts phrases waiting on 'am', 'have', 'will' and 'would', and
+        //the ones that fail don't take the others with them
+        assert_eq!(matches("I will go"), vec![(Action::Contraction, String::from("I will"))]);
+        assert_eq!(matches("I would go"), vec![(Action::Contraction, String::from("I would"))]);
+    }
+
+    #[test]
+    fn word_finishes_one_phrase_and_starts_another() {
+        assert_eq!(matches("I have not"), vec![
+            (Action::Contraction, String::from("I have")),
+            (Action::Contraction, String::from("have not")),
+        ]);
+    }
+
+    #[test]
+    fn whitespace_between_phrase_words() {
+        let database = init_database();
+        let mut matcher = Matcher::new();
+        let mut out = vec![];
+        for text in ["just", " ", "\n", "  ", "then"] {
+            out.extend(matcher.push_word(&Word::synthetic(text), &database, false));
+        }
+        assert_eq!(out.len(), 1);
+        let history: String = out[0].history.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(history, "just \n  then");
+    }
+
+    #[test]
+    fn broken_phrase_does_not_match() {
+        assert_eq!(matches("just now then"), vec![]);
+    }
+
+    #[test]
+    fn resets_at_sentence_boundary() {
+        assert!(findings("She was just. Then she left.\n", Action::WeakImmediacy).is_empty());
+        assert_eq!(findings("She was there just then.\n", Action::WeakImmediacy), vec!["just then"]);
+    }
+}
@@ -1,11 +1,14 @@
-use std::{io::Read, fs::File, path::Path, collections::HashMap};
+use std::{io::{Read, Write}, fs::{self, File}, path::Path, collections::HashMap};
 
-use quick_xml::{Reader, events::{Event, BytesStart}, name::QName};
-use zip::ZipArchive;
-use unicode_segmentation::UnicodeSegmentation;
+use quick_xml::{Reader, Writer, events::{Event, BytesStart, BytesText}, name::QName};
+use zip::{ZipArchive, ZipWriter, CompressionMethod, result::ZipError, write::FileOptions};
 
-use crate::document::Document;
-use crate::document::DocumentElement;
+use crate::builder::{paragraph_is_section_break, DocumentBuilder};
+use crate::config::Config;
+use crate::error::TeastainError;
+use crate::document::{Document, Paragraph};
+use crate::fixes::{self, Fix};
+use crate::suppress::Suppression;
 
 #[derive(Debug)]
 #[derive(PartialEq)]
@@ -16,10 +19,13 @@ enum TextAlign{
 
 #[derive(Debug)]
 enum DocumentElementODT{
+    ParagraphStart(String),
     ParagraphStyle(TextAlign),
     ParagraphEnd,
     ChapterEnd,
     Text(String),
+    /// The text of a comment attached to the paragraph.
+    Annotation(String),
 }
 
 
@@ -34,187 +40,312 @@ pub struct TextStyleODT{
 
 }
 
-fn paragraph_is_section_break(line: &String) -> bool {
-    let trimmed_line = line.trim();
-    trimmed_line == "* * *" || trimmed_line == "#"
+/// Tracks where the reader is in the XML tree, so that paragraphs can say
+/// where they came from.
+struct XmlPath {
+    elements: Vec<String>,
+    //for each element we're in, how many children of each name it has had
+    child_counts: Vec<HashMap<String, usize>>,
+}
+
+impl XmlPath {
+    fn new() -> XmlPath {
+        XmlPath { elements: vec![], child_counts: vec![HashMap::new()] }
+    }
+
+    //the path to the next child with this name, which doesn't have children
+    //of its own
+    fn child(&mut self, name: &[u8]) -> String {
+        let name = String::from_utf8_lossy(name).into_owned();
+        let count = self.child_counts.last_mut().unwrap().entry(name.clone()).or_insert(0);
+        *count += 1;
+        let mut out = String::from("");
+        for element in &self.elements {
+            out += "/";
+            out += element;
+        }
+        out + "/" + &name + "[" + &count.to_string() + "]"
+    }
+
+    fn start(&mut self, name: &[u8]) -> String {
+        let out = self.child(name);
+        self.elements.push(out.rsplit('/').next().unwrap().to_string());
+        self.child_counts.push(HashMap::new());
+        out
+    }
+
+    fn end(&mut self) {
+        self.elements.pop();
+        self.child_counts.pop();
+    }
+}
+
+//styles we've already warned about, so each is only reported once
+fn warn_unknown_style(style_name: &str, warnings: &mut Vec<TeastainError>) {
+    if !warnings.iter().any(|w| matches!(w, TeastainError::UnknownStyle(name) if name == style_name)) {
+        warnings.push(TeastainError::UnknownStyle(style_name.to_string()));
+    }
+}
+
+//a style:style element, which may be a paragraph style we need to know
+//about
+fn start_style(
+    e: &BytesStart,
+    current_style: &mut String,
+    paragraph_styles: &mut HashMap<String, ParagraphStyleODT>,
+    text_styles: &mut HashMap<String, TextStyleODT>
+) -> Result<(), quick_xml::Error> {
+    let mut style_name: String = String::from("");
+    let mut style_family = String::from("");
+    for a in e.attributes() {
+        let att = a?;
+        let att_name = att.key;
+        if att_name == QName(b"style:name") {
+            style_name = att.unescape_value()?.into_owned();
+        } else if att_name == QName(b"style:family") {
+            style_family = att.unescape_value()?.into_owned();
+        }
+    }
+    current_style.clone_from(&style_name);
+    if style_family == "paragraph" {
+        paragraph_styles.insert(style_name, ParagraphStyleODT{
+            begins_in_page_break: false,
+            center_aligned: false
+        });
+    } else if style_family == "text" {
+        text_styles.insert(style_name, TextStyleODT{});
+    }
+    Ok(())
+}
+
+//the style:paragraph-properties of the style we're in
+fn paragraph_properties(
+    e: &BytesStart,
+    current_style: &str,
+    paragraph_styles: &mut HashMap<String, ParagraphStyleODT>
+) -> Result<(), quick_xml::Error> {
+    //only paragraph styles are tracked
+    let Some(paragraph_style) = paragraph_styles.get_mut(current_style) else {
+        return Ok(());
+    };
+    for a in e.attributes() {
+        let att = a?;
+        let att_name = att.key;
+        if att_name == QName(b"fo:text-align") {
+            let align = att.unescape_value()?;
+            if align == "center" {
+                paragraph_style.center_aligned = true;
+            }
+        } else if att_name == QName(b"fo:break-before") {
+            let break_before = att.unescape_value()?;
+            if break_before == "page" {
+                paragraph_style.begins_in_page_break = true;
+            }
+        }
+    }
+    Ok(())
 }
 
 fn process_text_p_odt(
     e: BytesStart,
+    path: String,
     paragraph_styles: &HashMap<String, ParagraphStyleODT>,
-    document: &mut Vec<DocumentElementODT>
-) {
+    document: &mut Vec<DocumentElementODT>,
+    warnings: &mut Vec<TeastainError>
+) -> Result<(), quick_xml::Error> {
+    document.push(DocumentElementODT::ParagraphStart(path));
     for a in e.attributes() {
-        let att = a.unwrap();
+        let att = a?;
         let att_name = att.key;
         if att_name == QName(b"text:style-name") {
-            let style_name = att.unescape_value().unwrap().into_owned().to_string();
-            let paragraph_style = paragraph_styles.get(&style_name).unwrap();
-            if paragraph_style.begins_in_page_break {
-                document.push(DocumentElementODT::ChapterEnd);
-            }
-            if paragraph_style.center_aligned {
-                document.push(DocumentElementODT::ParagraphStyle(TextAlign::Centre));
-            } else {
-                document.push(DocumentElementODT::ParagraphStyle(TextAlign::Left));
+            let style_name = att.unescape_value()?.into_owned();
+            match paragraph_styles.get(&style_name) {
+                Some(paragraph_style) => {
+                    if paragraph_style.begins_in_page_break {
+                        document.push(DocumentElementODT::ChapterEnd);
+                    }
+                    if paragraph_style.center_aligned {
+                        document.push(DocumentElementODT::ParagraphStyle(TextAlign::Centre));
+                    } else {
+                        document.push(DocumentElementODT::ParagraphStyle(TextAlign::Left));
+                    }
+                }
+                //treat it as a plain paragraph
+                None => {
+                    warn_unknown_style(&style_name, warnings);
+                    document.push(DocumentElementODT::ParagraphStyle(TextAlign::Left));
+                }
             }
         } 
     }
+    Ok(())
 }
 
-fn parse_odt(path: &Path) -> Vec<DocumentElementODT> {
-    let file = File::open(path).unwrap();
-    let mut archive = ZipArchive::new(file).unwrap();
+const CONTENT_NAME: &str = "content.xml";
+const STYLES_NAME: &str = "styles.xml";
 
+fn read_content(archive: &mut ZipArchive<File>) -> Result<String, TeastainError> {
     let mut xml_data = String::new();
-    let content_name = "content.xml";
-    
-    let mut document: Vec<DocumentElementODT> = vec![];
+    match archive.by_name(CONTENT_NAME) {
+        Ok(mut c_file) => {
+            c_file.read_to_string(&mut xml_data)?;
+        }
+        Err(ZipError::FileNotFound) => return Err(TeastainError::MissingContent),
+        Err(e) => return Err(e.into()),
+    }
+    Ok(xml_data)
+}
 
-    let mut paragraph_styles: HashMap<String, ParagraphStyleODT> = HashMap::new();
-    let mut text_styles = HashMap::new();
+//the named styles, like 'Standard', which content.xml's paragraphs can use
+//as well as its own automatic styles
+fn read_styles(
+    archive: &mut ZipArchive<File>,
+    paragraph_styles: &mut HashMap<String, ParagraphStyleODT>,
+    text_styles: &mut HashMap<String, TextStyleODT>
+) -> Result<(), TeastainError> {
+    let mut xml_data = String::new();
+    match archive.by_name(STYLES_NAME) {
+        Ok(mut s_file) => {
+            s_file.read_to_string(&mut xml_data)?;
+        }
+        //it's optional
+        Err(ZipError::FileNotFound) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
 
-    for i in 0..archive.len() {
-        let mut c_file = archive.by_index(i).unwrap();
-        if c_file.name() == content_name {
-            c_file.read_to_string(&mut xml_data).unwrap();
-            break;
+    let mut reader = Reader::from_str(&xml_data);
+    let mut buf = Vec::new();
+    let mut current_style = String::from("");
+    loop {
+        let event = reader.read_event_into(&mut buf);
+        let position = reader.buffer_position();
+        let xml_error = |error| TeastainError::Xml { position, error };
+        match event {
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(xml_error(e)),
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"style:style" =>
+                    start_style(&e, &mut current_style, paragraph_styles, text_styles).map_err(xml_error)?,
+                b"style:paragraph-properties" =>
+                    paragraph_properties(&e, &current_style, paragraph_styles).map_err(xml_error)?,
+                _ => (),
+            },
+            _ => (),
         }
+        buf.clear();
     }
+    Ok(())
+}
+
+fn parse_odt(path: &Path, warnings: &mut Vec<TeastainError>) -> Result<Vec<DocumentElementODT>, TeastainError> {
+    let file = File::open(path)?;
+    let mut archive = ZipArchive::new(file)?;
+    let xml_data = read_content(&mut archive)?;
+
+    let mut document: Vec<DocumentElementODT> = vec![];
+
+    let mut paragraph_styles: HashMap<String, ParagraphStyleODT> = HashMap::new();
+    let mut text_styles = HashMap::new();
+    read_styles(&mut archive, &mut paragraph_styles, &mut text_styles)?;
 
     let mut reader = Reader::from_str(&xml_data);
 
     let mut buf = Vec::new();
     let mut current_style = String::from("");
-
-    if xml_data.len() > 0 {
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Eof) => break,
-
-                Err(e) => panic!(
-                    "Error at position {}: {:?}",
-                    reader.buffer_position(),
-                    e
-                ),
-
-                Ok(Event::Text(e)) => {
-                    let text = e.unescape().unwrap().into_owned();
-                    //we seem to get stray '\n's sometimes. We can ignore them
-                    //because ODT tells there are user-inserted paragraph ends 
-                    //by inserting text:p s
-                    if text != "\n" {
-                        document.push(DocumentElementODT::Text(text));
+    let mut path = XmlPath::new();
+    //the text of the comment we're in, if we're in one. Comments have
+    //their own text:ps, which aren't part of the manuscript
+    let mut annotation: Option<String> = None;
+    //and whether we're in one of its paragraphs, rather than its author or
+    //date
+    let mut in_annotation_p = false;
+
+    loop {
+        let event = reader.read_event_into(&mut buf);
+        let position = reader.buffer_position();
+        let xml_error = |error| TeastainError::Xml { position, error };
+        match event {
+            Ok(Event::Eof) => break,
+
+            Err(e) => return Err(xml_error(e)),
+
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().map_err(xml_error)?.into_owned();
+                //we seem to get stray '\n's sometimes. We can ignore them
+                //because ODT tells there are user-inserted paragraph ends 
+                //by inserting text:p s
+                if let Some(annotation) = &mut annotation {
+                    if in_annotation_p {
+                        *annotation += &text;
                     }
+                } else if text != "\n" {
+                    document.push(DocumentElementODT::Text(text));
                 }
-                    
-                Ok(Event::Empty(e)) => {
-                    match e.name().as_ref() {
-                        b"style:paragraph-properties" => {
-                            for a in e.attributes() {
-                                let att = a.unwrap();
-                                let att_name = att.key;
-                                if att_name == QName(b"fo:text-align") {
-                                    let align = att.unescape_value().unwrap();
-                                    if align == "center" {
-                                        paragraph_styles.get_mut(&current_style).unwrap().center_aligned = true;
-                                    }
-                                } else if att_name == QName(b"fo:break-before") {
-                                    let break_before = att.unescape_value().unwrap();
-                                    if break_before == "page" {
-                                        paragraph_styles.get_mut(&current_style).unwrap().begins_in_page_break = true;
-                                    }
-                                }
-                            }
-                        },
-                        b"text:p" => process_text_p_odt(e, &paragraph_styles, &mut document),
-                        _ => {},
-                    }
+            }
+                
+            Ok(Event::Empty(e)) => {
+                let element_path = path.child(e.name().as_ref());
+                match e.name().as_ref() {
+                    b"style:style" =>
+                        start_style(&e, &mut current_style, &mut paragraph_styles, &mut text_styles).map_err(xml_error)?,
+                    b"style:paragraph-properties" =>
+                        paragraph_properties(&e, &current_style, &mut paragraph_styles).map_err(xml_error)?,
+                    b"text:p" if annotation.is_none() =>
+                        process_text_p_odt(e, element_path, &paragraph_styles, &mut document, warnings).map_err(xml_error)?,
+                    _ => {},
                 }
+            }
 
-                Ok(Event::Start(e)) => {
-                    match e.name().as_ref() {
-                        b"style:style" => {
-                            let mut style_name: String = String::from("");
-                            let mut style_family = String::from("");
-                            for a in e.attributes() {
-                                let att = a.unwrap();
-                                let att_name = att.key;
-                                if att_name == QName(b"style:name") {
-                                    style_name = att.unescape_value().unwrap().into_owned().to_string();
-                                } else if att_name == QName(b"style:family") {
-                                    style_family = att.unescape_value().unwrap().into_owned().to_string();
-                                }
-                            }
-                            current_style = style_name.clone();
-                            if style_family == "paragraph" {
-                                paragraph_styles.insert(style_name, ParagraphStyleODT{
-                                    begins_in_page_break: false, 
-                                    center_aligned: false
-                                });
-                            } else if style_family == "text" {
-                                text_styles.insert(style_name, TextStyleODT{});
-                            }
-                        },
-                        b"text:p" => process_text_p_odt(e, &paragraph_styles, &mut document),
-                        _ => {},
-                    }
+            Ok(Event::Start(e)) => {
+                let element_path = path.start(e.name().as_ref());
+                match e.name().as_ref() {
+                    b"style:style" =>
+                        start_style(&e, &mut current_style, &mut paragraph_styles, &mut text_styles).map_err(xml_error)?,
+                    b"office:annotation" => annotation = Some(String::from("")),
+                    b"text:p" if annotation.is_some() => in_annotation_p = true,
+                    b"text:p" => process_text_p_odt(e, element_path, &paragraph_styles, &mut document, warnings).map_err(xml_error)?,
+                    _ => {},
                 }
+            }
 
-                Ok(Event::End(e)) => {
-                    match e.name().as_ref() {
-                        b"text:p" => document.push(DocumentElementODT::ParagraphEnd),
-                        _ => {}
+            Ok(Event::End(e)) => {
+                path.end();
+                match e.name().as_ref() {
+                    b"office:annotation" => {
+                        if let Some(text) = annotation.take() {
+                            document.push(DocumentElementODT::Annotation(text));
+                        }
+                    }
+                    b"text:p" if annotation.is_some() => {
+                        in_annotation_p = false;
+                        if let Some(annotation) = &mut annotation {
+                            annotation.push('\n');
+                        }
                     }
+                    b"text:p" => document.push(DocumentElementODT::ParagraphEnd),
+                    _ => {},
                 }
-
-                _ => (),
             }
-        }
-    }
-
-    document
-}
 
-fn parse_paragraph(paragraph: &String) -> Vec<DocumentElement>{
-    let words = paragraph.split_word_bounds();
-    let mut out = vec![];
-
-    let mut sentence: Vec<String> = vec![];
-    let mut direct_speech = false;
-    for word in words {
-        //skip leading space
-        if word.trim() == "" && sentence.len() == 0 {
-            continue;
-        }
-        if word == "‘" {
-            direct_speech = true;
-        } else if word == "’" && direct_speech {
-            out.push(DocumentElement::DirectSpeech(sentence));
-            direct_speech = false;
-            sentence = vec![];
-        } else if (word == "." || word == "!" || word == "?" || word == "…") && !direct_speech {
-            sentence.push(word.to_string());
-            out.push(DocumentElement::Sentence(sentence));
-            sentence = vec![];
-        } else {
-            sentence.push(word.to_string());
+            _ => (),
         }
     }
-    if sentence.len() > 0 {
-        out.push(DocumentElement::Sentence(sentence));
-    }
-    out.push(DocumentElement::ParagraphEnd);
-    out
+
+    Ok(document)
 }
 
-pub fn parse(path: &Path) -> Document {
-    let doc_in = parse_odt(&path);
-    //let doc_in: &Vec<DocumentElementODT>
-    let mut doc_out: Vec<DocumentElement> = vec![];
-    let mut number_of_chapters = 1;
+/// Read an ODT manuscript. Problems that don't stop us reading it are
+/// returned as the document's warnings.
+pub fn parse(path: &Path, config: &Config) -> Result<Document, TeastainError> {
+    let mut warnings = vec![];
+    let doc_in = parse_odt(path, &mut warnings)?;
+    let mut builder = DocumentBuilder::new(config);
 
     let mut paragraph_text = String::from("");
+    //every text:p counts, even the empty ones we otherwise ignore
+    let mut paragraph_count = 0;
+    let mut paragraph_index = 0;
+    let mut paragraph_path = String::from("");
 
     let mut after_page_break = true;
     let mut after_chapter_title = false;
@@ -223,6 +354,12 @@ pub fn parse(path: &Path) -> Document {
 
     for element in doc_in {
         match element {
+            DocumentElementODT::ParagraphStart(path) => {
+                paragraph_index = paragraph_count;
+                paragraph_count += 1;
+                paragraph_path = path;
+            },
+
             DocumentElementODT::ParagraphStyle(align) => {
                 if after_page_break && align == TextAlign::Centre {
                     chapter_title = true;
@@ -234,20 +371,26 @@ pub fn parse(path: &Path) -> Document {
 
             DocumentElementODT::ParagraphEnd => {
                 if paragraph_is_section_break(&paragraph_text) {
-                    doc_out.push(DocumentElement::SectionEnd);   
+                    builder.section_end(&paragraph_text);
                     chapter_title = false;
                     after_chapter_title = false;
                     chapter_name = false;
                 } else if chapter_title {
-                    doc_out.push(DocumentElement::ChapterTitle(paragraph_text));   
+                    builder.chapter_title(paragraph_text);
                     after_chapter_title = true; 
                     chapter_title = false;
                 } else if chapter_name {
-                    doc_out.push(DocumentElement::ChapterName(paragraph_text));   
+                    builder.chapter_name(paragraph_text);
                     chapter_name = false;
                 } else {
-                    let mut sentences = parse_paragraph(&paragraph_text);
-                    doc_out.append(&mut sentences);
+                    builder.paragraph(Paragraph {
+                        index: paragraph_index,
+                        text: paragraph_text,
+                        file: path.display().to_string(),
+                        source_path: paragraph_path.clone(),
+                        offset: 0,
+                        suppressions: vec![],
+                    });
                 }
                 
                 after_page_break = false;
@@ -256,15 +399,312 @@ pub fn parse(path: &Path) -> Document {
 
             DocumentElementODT::ChapterEnd => {
                 after_page_break = true;
-                doc_out.push(DocumentElement::ChapterEnd);
-                number_of_chapters += 1;
+                builder.chapter_end();
             }
 
             DocumentElementODT::Text(this_text) => {
                 paragraph_text = paragraph_text + &this_text;
             }
+
+            DocumentElementODT::Annotation(comment) => {
+                match Suppression::parse(&comment) {
+                    Some(Ok(suppression)) => builder.suppress(suppression),
+                    Some(Err(word)) => warnings.push(TeastainError::InvalidSuppression(word)),
+                    None => (),
+                }
+            }
         }
     }
 
-    return Document{ elements: doc_out, number_of_chapters: number_of_chapters };
-}
\ No newline at end of file
+    let mut document = builder.finish();
+    document.warnings = warnings;
+    Ok(document)
+}
+
+//the text of each paragraph, by its path, as parse reads it
+fn paragraph_texts(elements: &[DocumentElementODT]) -> HashMap<&str, String> {
+    let mut out: HashMap<&str, String> = HashMap::new();
+    let mut current = None;
+    for element in elements {
+        match element {
+            DocumentElementODT::ParagraphStart(path) => {
+                current = Some(path.as_str());
+                out.insert(path, String::from(""));
+            }
+            DocumentElementODT::Text(text) => {
+                if let Some(paragraph) = current.and_then(|path| out.get_mut(path)) {
+                    *paragraph += text;
+                }
+            }
+            _ => {},
+        }
+    }
+    out
+}
+
+//one text node of a paragraph, starting `at` bytes into the paragraph's
+//text, with the fixes made to it. A fix that runs over several nodes, say
+//from plain text into italics, puts its replacement in the first and takes
+//its text out of the rest, so the formatting around it is kept
+fn fix_text_node(text: &str, at: usize, fixes: &[&Fix]) -> Option<String> {
+    let end = at + text.len();
+    let touching: Vec<&&Fix> = fixes.iter().filter(|fix| fix.span.start < end && fix.span.end > at).collect();
+    if touching.is_empty() {
+        return None;
+    }
+    let mut out = String::from("");
+    let mut from = 0;
+    for fix in touching {
+        let local_start = fix.span.start.max(at) - at;
+        let local_end = fix.span.end.min(end) - at;
+        out += &text[from..local_start];
+        if fix.span.start >= at {
+            out += &fix.replacement;
+        }
+        from = local_end;
+    }
+    out += &text[from..];
+    Some(out)
+}
+
+//content.xml with the fixes made, and everything else as it was
+fn rewrite_content(xml_data: &str, fixes: &[&Fix]) -> Result<String, TeastainError> {
+    let mut reader = Reader::from_str(xml_data);
+    let mut writer = Writer::new(Vec::new());
+    let mut path = XmlPath::new();
+    let mut in_annotation = false;
+    //the fixes for the paragraph we're in, and how far into its text we are
+    let mut paragraph_fixes: Vec<&Fix> = vec![];
+    let mut at = 0;
+
+    loop {
+        let event = reader.read_event();
+        let position = reader.buffer_position();
+        let xml_error = |error| TeastainError::Xml { position, error };
+        let event = match event {
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(xml_error(e)),
+            Ok(event) => event,
+        };
+
+        match &event {
+            Event::Start(e) => {
+                let element_path = path.start(e.name().as_ref());
+                match e.name().as_ref() {
+                    b"office:annotation" => in_annotation = true,
+                    b"text:p" if !in_annotation => {
+                        paragraph_fixes = fixes.iter().copied().filter(|fix| fix.source_path == element_path).collect();
+                        at = 0;
+                    }
+                    _ => {},
+                }
+            }
+            Event::Empty(e) => {
+                path.child(e.name().as_ref());
+            }
+            Event::End(e) => {
+                path.end();
+                match e.name().as_ref() {
+                    b"office:annotation" => in_annotation = false,
+                    b"text:p" if !in_annotation => paragraph_fixes.clear(),
+                    _ => {},
+                }
+            }
+            Event::Text(e) if !in_annotation && !paragraph_fixes.is_empty() => {
+                let text = e.unescape().map_err(xml_error)?;
+                //parse skips these, so they aren't in the paragraph's text
+                if text != "\n" {
+                    let fixed = fix_text_node(&text, at, &paragraph_fixes);
+                    at += text.len();
+                    if let Some(fixed) = fixed {
+                        writer.write_event(Event::Text(BytesText::new(&fixed))).map_err(xml_error)?;
+                        continue;
+                    }
+                }
+            }
+            _ => {},
+        }
+        writer.write_event(event).map_err(xml_error)?;
+    }
+
+    Ok(String::from_utf8_lossy(&writer.into_inner()).into_owned())
+}
+
+/// Make fixes to an ODT manuscript, by editing the text in content.xml and
+/// leaving its markup, and so its formatting, alone. Everything else in the
+/// file is copied as it was.
+pub fn apply_fixes(path: &Path, fixes: &[Fix]) -> Result<usize, TeastainError> {
+    let mut warnings = vec![];
+    let elements = parse_odt(path, &mut warnings)?;
+    let texts = paragraph_texts(&elements);
+    let usable = fixes::usable(fixes, |fix| {
+        texts.get(fix.source_path.as_str()).and_then(|text| text.get(fix.span.start..fix.span.end))
+    });
+    if usable.is_empty() {
+        return Ok(0);
+    }
+
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let content = rewrite_content(&read_content(&mut archive)?, &usable)?;
+
+    //write the new file alongside the old one, so a failure part way
+    //through doesn't lose the manuscript
+    let temp_path = path.with_extension("odt.teastain-tmp");
+    let mut zip = ZipWriter::new(File::create(&temp_path)?);
+    for i in 0..archive.len() {
+        let name = archive.by_index_raw(i)?.name().to_string();
+        if name == CONTENT_NAME {
+            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+            zip.start_file(name, options)?;
+            zip.write_all(content.as_bytes())?;
+        } else {
+            zip.raw_copy_file(archive.by_index_raw(i)?)?;
+        }
+    }
+    zip.finish()?;
+    fs::rename(&temp_path, path)?;
+    Ok(usable.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::checker::{Action, Checker};
+
+    const NAMESPACES: &str = "xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+        xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" \
+        xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" \
+        xmlns:fo=\"urn:oasis:names:xmlns:xsl-fo-compatible:1.0\"";
+
+    //an ODT file with these automatic styles and paragraphs in its
+    //content.xml, and these named styles in its styles.xml
+    fn write_odt(name: &str, automatic_styles: &str, paragraphs: &str, styles: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("teastain-{}-{}.odt", name, std::process::id()));
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default();
+        zip.start_file("mimetype", options.compression_method(CompressionMethod::Stored)).unwrap();
+        zip.write_all(b"application/vnd.oasis.opendocument.text").unwrap();
+        zip.start_file(CONTENT_NAME, options).unwrap();
+        write!(zip, "<office:document-content {}><office:automatic-styles>{}</office:automatic-styles>\
+            <office:body><office:text>{}</office:text></office:body></office:document-content>",
+            NAMESPACES, automatic_styles, paragraphs).unwrap();
+        zip.start_file(STYLES_NAME, options).unwrap();
+        write!(zip, "<office:document-styles {}><office:styles>{}</office:styles></office:document-styles>",
+            NAMESPACES, styles).unwrap();
+        zip.finish().unwrap();
+        path
+    }
+
+    fn unknown_styles(document: &Document) -> Vec<String> {
+        document.warnings.iter()
+            .filter_map(|w| match w {
+                TeastainError::UnknownStyle(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn named_styles_are_known() {
+        let path = write_odt(
+            "named-styles",
+            "",
+            "<text:p text:style-name=\"Standard\">She ran.</text:p>\
+            <text:p text:style-name=\"Text_20_body\">She fell.</text:p>",
+            "<style:style style:name=\"Standard\" style:family=\"paragraph\"/>\
+            <style:style style:name=\"Text_20_body\" style:family=\"paragraph\">\
+            <style:paragraph-properties fo:margin-top=\"0in\"/></style:style>",
+        );
+        let document = parse(&path, &Config::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(unknown_styles(&document).is_empty());
+        assert_eq!(document.text(), "She ran.\n\nShe fell.\n");
+    }
+
+    #[test]
+    fn unknown_styles_are_reported_once() {
+        let path = write_odt(
+            "unknown-styles",
+            "",
+            "<text:p text:style-name=\"Mystery\">She ran.</text:p>\
+            <text:p text:style-name=\"Mystery\">She fell.</text:p>",
+            "<style:style style:name=\"Standard\" style:family=\"paragraph\"/>",
+        );
+        let document = parse(&path, &Config::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(unknown_styles(&document), vec!["Mystery"]);
+    }
+
+    #[test]
+    fn named_styles_can_start_chapters() {
+        let path = write_odt(
+            "named-chapter-style",
+            "",
+            "<text:p text:style-name=\"Standard\">She ran.</text:p>\
+            <text:p text:style-name=\"Chapter\">Two</text:p>\
+            <text:p text:style-name=\"Standard\">She fell.</text:p>",
+            "<style:style style:name=\"Standard\" style:family=\"paragraph\"/>\
+            <style:style style:name=\"Chapter\" style:family=\"paragraph\">\
+            <style:paragraph-properties fo:break-before=\"page\" fo:text-align=\"center\"/></style:style>",
+        );
+        let document = parse(&path, &Config::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(document.number_of_chapters, 2);
+        assert!(document.elements.iter().any(|e| matches!(e, crate::DocumentElement::ChapterTitle(title) if title == "Two")));
+    }
+    //every entry in an ODT file, by name, uncompressed
+    fn entries(path: &Path) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ZipArchive::new(File::open(path).unwrap()).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut bytes = vec![];
+                file.read_to_end(&mut bytes).unwrap();
+                (file.name().to_string(), bytes)
+            })
+            .collect()
+    }
+
+    fn automatic_styles(content: &[u8]) -> String {
+        let content = String::from_utf8_lossy(content);
+        let start = content.find("<office:automatic-styles").unwrap();
+        let end = content.find("</office:automatic-styles>").unwrap();
+        content[start..end].to_string()
+    }
+
+    #[test]
+    fn fixes_round_trip() {
+        let path = std::env::temp_dir().join(format!("teastain-round-trip-{}.odt", std::process::id()));
+        fs::copy("res/sd1.odt", &path).unwrap();
+        let before = entries(&path);
+        let checker = Checker::with_rules(Config::default(), vec![Action::Contraction]);
+        let fixes: Vec<Fix> = checker.check(&parse(&path, &Config::default()).unwrap())
+            .iter()
+            .filter_map(Fix::of)
+            .collect();
+        assert!(!fixes.is_empty());
+
+        let applied = apply_fixes(&path, &fixes).unwrap();
+        let document = parse(&path, &Config::default()).unwrap();
+        let after = entries(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(applied, fixes.len());
+        assert!(checker.check(&document).is_empty());
+        for fix in &fixes {
+            assert!(document.text().contains(&fix.replacement), "{}", fix.replacement);
+        }
+        assert_eq!(before.len(), after.len());
+        for ((name, bytes), (name_after, bytes_after)) in before.iter().zip(&after) {
+            assert_eq!(name, name_after);
+            if name == CONTENT_NAME {
+                assert_ne!(bytes, bytes_after);
+                assert_eq!(automatic_styles(bytes), automatic_styles(bytes_after));
+            } else {
+                assert!(bytes == bytes_after, "{} changed", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::checker::Location;
+use crate::document::Word;
+
+/// A run of consecutive sentences that open the same way.
+pub struct OpeningRun {
+    /// What the sentences have in common: their shared first words, or a
+    /// description of the shared structure.
+    pub opening: Vec<String>,
+    /// All the words of the run, with the sentences separated by spaces.
+    pub text: Vec<Word>,
+    /// Where the run starts.
+    pub location: Location,
+}
+
+fn opening_words(sentence: &[Word]) -> Vec<String> {
+    sentence.iter()
+        .filter(|w| w.text.chars().any(|c| c.is_alphanumeric()))
+        .take(2)
+        .map(|w| w.text.to_lowercase())
+        .collect()
+}
+
+//words ending in -ing that aren't participles, on top of 'something' and
+//the other words ending in -thing
+const NOT_PARTICIPLES: &[&str] = &[
+    "awning", "ceiling", "darling", "during", "evening", "morning", "notwithstanding", "pudding",
+    "sibling", "spring", "sting", "string", "swing", "viking", "wedding",
+];
+
+fn is_participle_opening(opening: &[String]) -> bool {
+    opening.first().is_some_and(|w| {
+        w.len() > 4 && w.ends_with("ing") && !w.ends_with("thing") && !NOT_PARTICIPLES.contains(&w.as_str())
+    })
+}
+
+/// Watches consecutive sentences for runs that start with the same word, the
+/// same two words, or an -ing participle.
+pub struct OpeningTracker {
+    min_run_length: usize,
+    run: Vec<(Vec<String>, Vec<Word>, Location)>,
+}
+
+impl OpeningTracker {
+    /// A tracker that reports runs of at least `min_run_length` sentences,
+    /// and never runs of a single sentence or none.
+    pub fn new(min_run_length: usize) -> OpeningTracker {
+        OpeningTracker { min_run_length: min_run_length.max(2), run: vec![] }
+    }
+
+    /// Add the next sentence, returning the run it brings to an end, if any.
+    pub fn push_sentence(&mut self, sentence: &[Word], location: &Location) -> Option<OpeningRun> {
+        let opening = opening_words(sentence);
+        if opening.is_empty() {
+            return self.finish();
+        }
+
+        let mut out = None;
+        if let Some((last_opening, _, _)) = self.run.last() {
+            let same_word = last_opening[0] == opening[0];
+            let same_structure = is_participle_opening(last_opening) && is_participle_opening(&opening);
+            if !same_word && !same_structure {
+                out = self.finish();
+            }
+        }
+        self.run.push((opening, sentence.to_vec(), location.clone()));
+        out
+    }
+
+    /// End whatever run is in progress, returning it if it is long enough to
+    /// report; used when consecutive sentences are interrupted, e.g. by speech
+    /// or a scene break.
+    pub fn finish(&mut self) -> Option<OpeningRun> {
+        let run: Vec<_> = self.run.drain(..).collect();
+        if run.is_empty() || run.len() < self.min_run_length {
+            return None;
+        }
+
+        let first = &run[0].0;
+        let opening = if !run.iter().all(|s| s.0[0] == first[0]) {
+            vec!["-ing participle".to_string()]
+        } else if first.len() == 2 && run.iter().all(|s| s.0 == *first) {
+            first.clone()
+        } else {
+            vec![first[0].clone()]
+        };
+
+        let mut text = vec![];
+        for (_, sentence, _) in &run {
+            if !text.is_empty() {
+                text.push(Word::synthetic(" "));
+            }
+            text.extend(sentence.iter().cloned());
+        }
+        Some(OpeningRun { opening, text, location: run[0].2.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checker::{check_markdown, findings, Action};
+    use crate::config::Config;
+
+    fn runs(text: &str) -> Vec<String> {
+        findings(text, Action::RepeatedOpening)
+    }
+
+    #[test]
+    fn participle_run() {
+        let text = "Turning, she ran. Running, she fell. Falling, she cried.\n";
+        assert_eq!(runs(text).len(), 1);
+    }
+
+    #[test]
+    fn words_ending_in_ing_that_are_not_participles() {
+        let text = "Something moved. During the night it rained. Nothing happened. Everything was quiet.\n";
+        assert!(runs(text).is_empty());
+    }
+
+    fn runs_with_length(text: &str, opening_run_length: usize) -> usize {
+        let config = Config { opening_run_length, ..Config::default() };
+        check_markdown(text, Action::RepeatedOpening, config).len()
+    }
+
+    #[test]
+    fn run_length_boundary() {
+        let text = "She ran. She fell. She cried.\n";
+        assert_eq!(runs_with_length(text, 3), 1);
+        assert_eq!(runs_with_length(text, 4), 0);
+        assert_eq!(runs_with_length("She ran. He fell.\n", 2), 0);
+    }
+
+    #[test]
+    fn run_length_of_zero_or_one() {
+        assert_eq!(runs_with_length("She ran.\n\nHe fell.\n", 0), 0);
+        assert_eq!(runs_with_length("She ran.\n\nHe fell.\n", 1), 0);
+        assert_eq!(runs_with_length("She ran. She fell.\n", 0), 1);
+    }
+}
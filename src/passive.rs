@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use crate::database::{DictionaryElem, PartOfSpeech};
+use crate::document::Word;
+
+fn is_passive_auxiliary(word: &str) -> bool {
+    matches!(word,
+        "am" | "is" | "are" | "was" | "were" | "be" | "been" | "being" |
+        "get" | "gets" | "got" | "gotten" | "getting"
+    )
+}
+
+//words that may sit between the auxiliary and the participle without
+//breaking the construction, e.g. 'was not opened', 'was quickly opened'
+fn is_passive_modifier(word: &str) -> bool {
+    word == "not" || word == "never" || word.ends_with("ly")
+}
+
+//words that can't be part of the agent, so end it
+fn ends_agent(word: &str) -> bool {
+    matches!(word,
+        "and" | "or" | "but" | "as" | "at" | "for" | "from" | "in" | "of" | "on" | "to" | "with" |
+        "who" | "which" | "that"
+    )
+}
+
+//past participles that are nearly always adjectives after 'to be', as in
+//'I am tired', unless they have a 'by' agent
+const ADJECTIVAL_PARTICIPLES: &[&str] = &[
+    "amazed", "amused", "annoyed", "ashamed", "bored", "concerned", "confused", "convinced",
+    "delighted", "determined", "disappointed", "done", "dressed", "embarrassed", "excited",
+    "exhausted", "frightened", "gone", "interested", "involved", "married", "pleased", "relieved",
+    "satisfied", "scared", "shocked", "surprised", "terrified", "thrilled", "tired", "worried",
+];
+
+fn is_past_participle(word: &str, dictionary: &HashMap<String, DictionaryElem>) -> bool {
+    if let Some(dictionary_elem) = dictionary.get(word) {
+        return dictionary_elem.part_of_speech == PartOfSpeech::PastParticiple;
+    }
+    word.len() > 3 && word.ends_with("ed") && word.chars().all(|c| c.is_alphabetic())
+}
+
+/// Find passive constructions: a form of 'to be' or 'get' followed by a past
+/// participle, optionally with a 'by' agent. Returns the words of each match.
+pub fn find_passive_voice(
+    sentence: &[Word],
+    dictionary: &HashMap<String, DictionaryElem>
+) -> Vec<Vec<Word>> {
+    let mut out = vec![];
+
+    //indices of the words that aren't whitespace, so we can look ahead
+    let words: Vec<usize> = (0..sentence.len()).filter(|i| !sentence[*i].text.trim().is_empty()).collect();
+    let lowercase_words: Vec<String> = words.iter().map(|i| sentence[*i].text.to_lowercase()).collect();
+
+    let mut i = 0;
+    while i < words.len() {
+        if !is_passive_auxiliary(&lowercase_words[i]) {
+            i += 1;
+            continue;
+        }
+
+        let mut j = i + 1;
+        if j < words.len() && is_passive_modifier(&lowercase_words[j]) {
+            j += 1;
+        }
+        if j >= words.len() || !is_past_participle(&lowercase_words[j], dictionary) {
+            i += 1;
+            continue;
+        }
+        let has_agent = j + 1 < words.len() && lowercase_words[j + 1] == "by";
+        if !has_agent && ADJECTIVAL_PARTICIPLES.contains(&lowercase_words[j].as_str()) {
+            i += 1;
+            continue;
+        }
+
+        //take the agent up to the next punctuation, but don't wander off
+        //into the rest of the sentence
+        let mut end = j;
+        if has_agent {
+            let mut k = j + 2;
+            while k < words.len() && k <= j + 4
+                && sentence[words[k]].text.chars().all(|c| c.is_alphabetic())
+                && !ends_agent(&lowercase_words[k]) {
+                end = k;
+                k += 1;
+            }
+        }
+
+        out.push(sentence[words[i]..=words[end]].to_vec());
+        i = end + 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{findings, Action};
+    use crate::database::init_dictionary;
+
+    fn passives(text: &str) -> Vec<String> {
+        findings(text, Action::PassiveVoice)
+    }
+
+    #[test]
+    fn passive_with_and_without_an_agent() {
+        assert_eq!(passives("The door was opened by him.\n"), vec!["was opened by him"]);
+        assert_eq!(passives("The window was broken.\n"), vec!["was broken"]);
+        assert_eq!(passives("He got fired on Monday.\n"), vec!["got fired"]);
+        assert_eq!(passives("The letter was not signed.\n"), vec!["was not signed"]);
+    }
+
+    #[test]
+    fn words_ending_in_ed_that_are_not_participles() {
+        let dictionary = init_dictionary();
+        for word in ["bleed", "feed", "hundred", "need", "seed", "speed", "naked", "wicked"] {
+            assert!(!is_past_participle(word, &dictionary), "{}", word);
+        }
+        assert!(is_past_participle("shed", &dictionary));
+        assert!(passives("The knight was wicked.\n").is_empty());
+        assert_eq!(passives("Blood was shed.\n"), vec!["was shed"]);
+    }
+
+    #[test]
+    fn adjectival_participles() {
+        assert!(passives("I am tired.\n").is_empty());
+        assert!(passives("She was excited about the trip.\n").is_empty());
+        assert!(passives("They were worried.\n").is_empty());
+        assert_eq!(passives("She was excited by the news.\n"), vec!["was excited by the news"]);
+    }
+}
@@ -0,0 +1,264 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Which quotation marks a manuscript uses for speech. The first pair of each
+/// convention marks speech; the second marks a quote nested inside it.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuoteConvention {
+    /// ‘Speech with “nested” quotes’
+    #[cfg_attr(feature = "serde", serde(rename = "uk"))]
+    UkSingle,
+    /// “Speech with ‘nested’ quotes”
+    #[cfg_attr(feature = "serde", serde(rename = "us"))]
+    UsDouble,
+    /// "Speech with 'nested' quotes"
+    #[cfg_attr(feature = "serde", serde(rename = "straight"))]
+    Straight,
+    /// «Speech with ‹nested› quotes»
+    #[cfg_attr(feature = "serde", serde(rename = "guillemets"))]
+    Guillemets,
+}
+
+impl QuoteConvention {
+    pub fn from_name(name: &str) -> Option<QuoteConvention> {
+        match name {
+            "uk" => Some(QuoteConvention::UkSingle),
+            "us" => Some(QuoteConvention::UsDouble),
+            "straight" => Some(QuoteConvention::Straight),
+            "guillemets" => Some(QuoteConvention::Guillemets),
+            _ => None,
+        }
+    }
+
+    /// The mark the manuscript uses for an apostrophe.
+    pub fn apostrophe(self) -> &'static str {
+        match self {
+            QuoteConvention::Straight => "'",
+            _ => "’",
+        }
+    }
+
+    fn pairs(self) -> [(&'static str, &'static str); 2] {
+        match self {
+            QuoteConvention::UkSingle => [("‘", "’"), ("“", "”")],
+            QuoteConvention::UsDouble => [("“", "”"), ("‘", "’")],
+            QuoteConvention::Straight => [("\"", "\""), ("'", "'")],
+            QuoteConvention::Guillemets => [("«", "»"), ("‹", "›")],
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum QuoteToken {
+    Word,
+    SpeechOpen,
+    SpeechClose,
+}
+
+//the marks that double as an apostrophe
+fn can_be_apostrophe(mark: &str) -> bool {
+    mark == "’" || mark == "'"
+}
+
+fn starts_with_letter(word: Option<&&str>) -> bool {
+    word.and_then(|w| w.chars().next()).is_some_and(|c| c.is_alphabetic())
+}
+
+fn ends_with_letter(word: Option<&&str>) -> bool {
+    word.and_then(|w| w.chars().last()).is_some_and(|c| c.is_alphabetic())
+}
+
+/// Splits paragraphs into words, telling the quotation marks that open and
+/// close speech from nested quotes and apostrophes. Nesting is tracked in
+/// the tokenizer, so one can be carried from paragraph to paragraph.
+#[derive(Debug)]
+pub struct QuoteTokenizer {
+    convention: QuoteConvention,
+    //the closing mark we expect for each quote we're inside, innermost last
+    open_quotes: Vec<&'static str>,
+    //speech was left open at the end of the last paragraph, and this one
+    //reopens it
+    continuing_speech: bool,
+}
+
+impl QuoteTokenizer {
+    pub fn new(convention: QuoteConvention) -> QuoteTokenizer {
+        QuoteTokenizer { convention, open_quotes: vec![], continuing_speech: false }
+    }
+
+    pub fn in_speech(&self) -> bool {
+        !self.open_quotes.is_empty()
+    }
+
+    /// Get ready for the next paragraph. When one speaker's speech runs over
+    /// several paragraphs, the convention is to leave the closing quote off
+    /// every paragraph but the last, and open each new one with a quote. So
+    /// speech that's still open carries on if the paragraph starts with an
+    /// opening quote; otherwise its quote was never closed, and this returns
+    /// true.
+    pub fn start_paragraph(&mut self, paragraph: &str) -> bool {
+        self.continuing_speech = false;
+        if self.open_quotes.is_empty() {
+            return false;
+        }
+
+        let (open, _) = self.convention.pairs()[0];
+        let first_word = paragraph.split_word_bounds().find(|w| !w.trim().is_empty());
+        if first_word == Some(open) {
+            //only the speech itself carries on, not anything nested in it
+            self.open_quotes.truncate(1);
+            self.continuing_speech = true;
+            false
+        } else {
+            self.open_quotes.clear();
+            true
+        }
+    }
+
+    /// Forget any quotes that are still open, returning true if there were any.
+    pub fn finish(&mut self) -> bool {
+        let unclosed = !self.open_quotes.is_empty();
+        self.open_quotes.clear();
+        unclosed
+    }
+
+    //a mark that could close the innermost quote, but could also be an
+    //apostrophe: "don’t", "’tis", a dropped letter as in "goin’ home", or the
+    //plural possessive "the dogs’ bowls"
+    fn is_apostrophe(&self, words: &[&str], i: usize) -> bool {
+        let mark = words[i];
+        if !can_be_apostrophe(mark) {
+            return false;
+        }
+        let previous = if i > 0 { words.get(i - 1) } else { None };
+        let next = words.get(i + 1);
+        //closing quotes come straight after the text they close
+        if previous.is_none_or(|w| w.trim().is_empty()) {
+            return true;
+        }
+        if ends_with_letter(previous) && starts_with_letter(next) {
+            return true;
+        }
+        //nor do they come straight after an opening quote, as in "‘’Tis"
+        let opening_marks = self.convention.pairs().map(|(open, _)| open);
+        if previous.is_some_and(|w| opening_marks.contains(w)) && starts_with_letter(next) {
+            return true;
+        }
+        //a dropped letter or a plural possessive at the end of a word is
+        //followed by more of the sentence; if the quote closes later in the
+        //paragraph, assume this isn't the close
+        if ends_with_letter(previous)
+            && next.is_some_and(|w| w.trim().is_empty())
+            && words.get(i + 2).and_then(|w| w.chars().next()).is_some_and(|c| c.is_lowercase()) {
+            return words[i + 1..].contains(&mark);
+        }
+        false
+    }
+
+    //a mark that could open a nested quote, but could also be an apostrophe
+    fn is_opening(&self, words: &[&str], i: usize) -> bool {
+        if !can_be_apostrophe(words[i]) {
+            return true;
+        }
+        let previous = if i > 0 { words.get(i - 1) } else { None };
+        previous.is_none_or(|w| !ends_with_letter(Some(w))) && words.get(i + 1).is_some_and(|w| !w.trim().is_empty())
+    }
+
+    /// Split a paragraph into tokens, each with its byte offset and text.
+    pub fn tokenize<'a>(&mut self, paragraph: &'a str) -> Vec<(usize, &'a str, QuoteToken)> {
+        let (offsets, words): (Vec<usize>, Vec<&str>) = paragraph.split_word_bound_indices().unzip();
+        let pairs = self.convention.pairs();
+        let mut out = vec![];
+
+        for i in 0..words.len() {
+            let word = words[i];
+            //speech alternates between the outer and nested marks as it nests
+            let (open, close) = pairs[self.open_quotes.len() % 2];
+
+            let token = if self.continuing_speech && !word.trim().is_empty() {
+                //the quote that reopens the speech
+                self.continuing_speech = false;
+                QuoteToken::SpeechOpen
+            } else if self.open_quotes.last() == Some(&word) && !self.is_apostrophe(&words, i) {
+                self.open_quotes.pop();
+                if self.open_quotes.is_empty() {
+                    QuoteToken::SpeechClose
+                } else {
+                    QuoteToken::Word
+                }
+            } else if word == open && self.is_opening(&words, i) {
+                self.open_quotes.push(close);
+                if self.open_quotes.len() == 1 {
+                    QuoteToken::SpeechOpen
+                } else {
+                    QuoteToken::Word
+                }
+            } else {
+                QuoteToken::Word
+            };
+            out.push((offsets[i], word, token));
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //the text of the first speech in a paragraph, and whether it was closed
+    fn speech(paragraph: &str) -> (String, bool) {
+        let mut tokenizer = QuoteTokenizer::new(QuoteConvention::UkSingle);
+        let mut out = String::from("");
+        let mut in_speech = false;
+        for (_, word, token) in tokenizer.tokenize(paragraph) {
+            match token {
+                QuoteToken::SpeechOpen => in_speech = true,
+                QuoteToken::SpeechClose => return (out, true),
+                QuoteToken::Word if in_speech => out += word,
+                QuoteToken::Word => (),
+            }
+        }
+        (out, false)
+    }
+
+    #[test]
+    fn apostrophe_inside_a_word() {
+        assert_eq!(speech("‘I don’t know,’ he said."), (String::from("I don’t know,"), true));
+    }
+
+    #[test]
+    fn dropped_letter_at_the_end_of_a_word() {
+        assert_eq!(speech("‘I’m goin’ home now,’ he said."), (String::from("I’m goin’ home now,"), true));
+    }
+
+    #[test]
+    fn dropped_letter_after_the_opening_quote() {
+        assert_eq!(speech("‘’Tis true,’ she said."), (String::from("’Tis true,"), true));
+    }
+
+    #[test]
+    fn dropped_letters_either_side_of_a_word() {
+        assert_eq!(speech("‘Rock ’n’ roll,’ he said."), (String::from("Rock ’n’ roll,"), true));
+    }
+
+    #[test]
+    fn plural_possessive() {
+        assert_eq!(speech("‘The dogs’ bowls are empty,’ she said."), (String::from("The dogs’ bowls are empty,"), true));
+    }
+
+    #[test]
+    fn nested_quote() {
+        assert_eq!(speech("‘He said “no” to me,’ she said."), (String::from("He said “no” to me,"), true));
+    }
+
+    #[test]
+    fn close_followed_by_a_tag() {
+        assert_eq!(speech("‘Go home’ she said."), (String::from("Go home"), true));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::stats::Counts;
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// Roughly how many syllables a word has: its runs of vowels, less a silent
+/// 'e' at the end and a silent 'e' in '-ed' and '-es'. Every word with a
+/// letter in has at least one.
+pub fn count_syllables(word: &str) -> usize {
+    let letters: Vec<char> = word.to_lowercase().chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return 0;
+    }
+    if letters.len() <= 3 {
+        return 1;
+    }
+
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in &letters {
+        let vowel = is_vowel(*c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    let n = letters.len();
+    let before = |from_end: usize| letters[n - from_end];
+    let silent_e = match (before(2), before(1)) {
+        //'make', but not 'table' or 'free'
+        (c, 'e') => !is_vowel(c) && (c != 'l' || is_vowel(before(3))),
+        //'jumped', but not 'wanted'
+        ('e', 'd') => !matches!(before(3), 't' | 'd'),
+        //'makes', but not 'boxes' or 'judges'
+        ('e', 's') => !matches!(before(3), 's' | 'x' | 'z' | 'c' | 'g' | 'h'),
+        _ => false,
+    };
+    if silent_e && count > 1 {
+        count -= 1;
+    }
+    //'brrr' and 'pssst' have no vowels but are still said
+    count.max(1)
+}
+
+/// The reading age for a US school grade.
+pub fn reading_age(grade: f64) -> f64 {
+    grade + 5.0
+}
+
+/// How hard some text is to read, by the usual formulas.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Readability {
+    /// From 0 to 100, higher being easier.
+    pub flesch_reading_ease: f64,
+    /// The US school grade.
+    pub flesch_kincaid_grade: f64,
+    pub gunning_fog: f64,
+    pub smog: f64,
+    pub coleman_liau: f64,
+    /// The reading age for the Flesch-Kincaid grade.
+    pub reading_age: f64,
+}
+
+impl Readability {
+    /// The scores for counted text. Text without any sentences scores 0.
+    pub fn of(counts: &Counts) -> Readability {
+        if counts.sentences == 0 || counts.words == 0 {
+            return Readability::default();
+        }
+        let sentences = counts.sentences as f64;
+        let words = counts.words as f64;
+        let words_per_sentence = words / sentences;
+        let syllables_per_word = counts.syllables as f64 / words;
+        let letters_per_100_words = 100.0 * counts.letters as f64 / words;
+        let sentences_per_100_words = 100.0 * sentences / words;
+
+        let flesch_kincaid_grade = 0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59;
+        Readability {
+            flesch_reading_ease: 206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word,
+            flesch_kincaid_grade,
+            gunning_fog: 0.4 * (words_per_sentence + 100.0 * counts.polysyllables as f64 / words),
+            smog: 1.043 * (counts.polysyllables as f64 * 30.0 / sentences).sqrt() + 3.1291,
+            coleman_liau: 0.0588 * letters_per_100_words - 0.296 * sentences_per_100_words - 15.8,
+            reading_age: reading_age(flesch_kincaid_grade),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syllables(words: &[(&str, usize)]) {
+        for (word, expected) in words {
+            assert_eq!(count_syllables(word), *expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn silent_e() {
+        syllables(&[("make", 1), ("smile", 1), ("escape", 2), ("free", 1)]);
+    }
+
+    #[test]
+    fn le_endings() {
+        syllables(&[("table", 2), ("little", 2), ("simple", 2), ("whale", 1), ("mile", 1)]);
+    }
+
+    #[test]
+    fn ed_endings() {
+        syllables(&[("jumped", 1), ("walked", 1), ("wanted", 2), ("landed", 2), ("bed", 1)]);
+    }
+
+    #[test]
+    fn es_endings() {
+        syllables(&[("makes", 1), ("boxes", 2), ("judges", 2), ("horses", 2)]);
+    }
+
+    #[test]
+    fn diphthongs() {
+        syllables(&[("rain", 1), ("boat", 1), ("cloud", 1), ("beautiful", 3), ("enjoy", 2)]);
+    }
+
+    #[test]
+    fn short_words() {
+        syllables(&[("a", 1), ("I", 1), ("O", 1), ("an", 1), ("the", 1)]);
+    }
+
+    #[test]
+    fn words_without_vowels() {
+        syllables(&[("myth", 1), ("hmm", 1), ("shh", 1), ("brrr", 1), ("pssst", 1), ("", 0), ("42", 0)]);
+    }
+}
@@ -0,0 +1,444 @@
+use std::{collections::HashMap, error::Error};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span as TextSpan};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::fingerprint;
+use crate::checker::Diagnostic;
+use crate::document::{Document, DocumentElement};
+
+/// What the author decided about a finding.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewStatus {
+    /// Seen, and left as it is.
+    Accepted,
+    /// Not to be reported again.
+    Ignored,
+    /// Fixed by making its suggestion.
+    Fixed,
+}
+
+/// How far a review has got, kept between sessions.
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Serialize, Deserialize)]
+pub struct ReviewProgress {
+    /// What was decided, by finding fingerprint.
+    pub statuses: HashMap<String, ReviewStatus>,
+    /// The fingerprint of the finding the review was at.
+    pub current: Option<String>,
+}
+
+impl ReviewProgress {
+    pub fn status(&self, diagnostic: &Diagnostic) -> Option<ReviewStatus> {
+        self.statuses.get(&fingerprint(diagnostic)).copied()
+    }
+
+    pub fn mark(&mut self, diagnostic: &Diagnostic, status: ReviewStatus) {
+        self.statuses.insert(fingerprint(diagnostic), status);
+    }
+}
+
+/// A finding, with the paragraph it's in.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Finding {
+    pub diagnostic: Diagnostic,
+    pub paragraph: String,
+}
+
+/// Pair findings in a document with the text of their paragraphs.
+pub fn findings(document: &Document, diagnostics: Vec<Diagnostic>) -> Vec<Finding> {
+    let mut paragraphs: HashMap<(&str, usize), &str> = HashMap::new();
+    for element in &document.elements {
+        if let DocumentElement::ParagraphStart(paragraph) = element {
+            paragraphs.insert((&paragraph.file, paragraph.index), &paragraph.text);
+        }
+    }
+    diagnostics.into_iter()
+        .map(|diagnostic| {
+            let key = (diagnostic.location.file.as_str(), diagnostic.location.paragraph_index);
+            let paragraph = paragraphs.get(&key).map_or(String::from(""), |text| text.to_string());
+            Finding { diagnostic, paragraph }
+        })
+        .collect()
+}
+
+/// What the review UI needs from whoever runs it.
+pub trait Review {
+    /// The findings to review, checked afresh.
+    fn findings(&mut self) -> Result<Vec<Finding>, String>;
+    fn progress(&mut self) -> &mut ReviewProgress;
+    /// Ignore or fix a finding. Accepting one only needs it marking in the
+    /// progress.
+    fn act(&mut self, diagnostic: &Diagnostic, status: ReviewStatus) -> Result<(), String>;
+    /// Write the progress out.
+    fn save(&mut self) -> Result<(), String>;
+}
+
+//a line of the list: a chapter heading, or a finding by its index
+enum Row {
+    Chapter(String),
+    Finding(usize),
+}
+
+fn chapter_heading(diagnostic: &Diagnostic) -> String {
+    let location = &diagnostic.location;
+    let heading = if !location.chapter_title.is_empty() {
+        location.chapter_title.clone()
+    } else {
+        format!("Chapter {}", location.chapter_number)
+    };
+    format!("{} ({})", heading, location.file)
+}
+
+fn rows(findings: &[Finding]) -> Vec<Row> {
+    let mut out = vec![];
+    let mut chapter = None;
+    for (i, finding) in findings.iter().enumerate() {
+        let location = &finding.diagnostic.location;
+        let this_chapter = (location.file.as_str(), location.chapter_number);
+        if chapter != Some(this_chapter) {
+            out.push(Row::Chapter(chapter_heading(&finding.diagnostic)));
+            chapter = Some(this_chapter);
+        }
+        out.push(Row::Finding(i));
+    }
+    out
+}
+
+//the paragraph, with the finding's words picked out
+fn highlighted(finding: &Finding) -> Line<'_> {
+    let span = finding.diagnostic.location.span;
+    let text = &finding.paragraph;
+    match (text.get(..span.start), text.get(span.start..span.end), text.get(span.end..)) {
+        (Some(before), Some(found), Some(after)) => Line::from(vec![
+            TextSpan::raw(before),
+            TextSpan::styled(found, Style::new().yellow().bold().underlined()),
+            TextSpan::raw(after),
+        ]),
+        _ => Line::from(text.as_str()),
+    }
+}
+
+struct App<'a> {
+    review: &'a mut dyn Review,
+    findings: Vec<Finding>,
+    selected: usize,
+    message: String,
+}
+
+impl App<'_> {
+    fn load(&mut self) -> Result<(), String> {
+        self.findings = self.review.findings()?;
+        self.selected = self.selected.min(self.findings.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn current(&self) -> Option<&Finding> {
+        self.findings.get(self.selected)
+    }
+
+    //carry on from the finding the last session was at, or failing that the
+    //first one that hasn't been dealt with
+    fn resume(&mut self) {
+        let current = self.review.progress().current.clone();
+        let at_current = self.findings.iter().position(|f| Some(fingerprint(&f.diagnostic)) == current);
+        self.selected = match at_current {
+            Some(i) => i,
+            None => self.next_unreviewed(0).unwrap_or(0),
+        };
+    }
+
+    fn next_unreviewed(&mut self, from: usize) -> Option<usize> {
+        let progress = self.review.progress();
+        (from..self.findings.len()).find(|i| progress.status(&self.findings[*i].diagnostic).is_none())
+    }
+
+    fn decide(&mut self, status: ReviewStatus) -> Result<(), String> {
+        let Some(finding) = self.current() else {
+            return Ok(());
+        };
+        let diagnostic = finding.diagnostic.clone();
+        if status == ReviewStatus::Fixed && diagnostic.suggestion.is_none() {
+            self.message = String::from("No fix for this finding");
+            return Ok(());
+        }
+        self.review.act(&diagnostic, status)?;
+        self.review.progress().mark(&diagnostic, status);
+        self.review.save()?;
+        self.message = match status {
+            ReviewStatus::Accepted => String::from("Accepted"),
+            ReviewStatus::Ignored => String::from("Ignored, and added to the baseline"),
+            ReviewStatus::Fixed => String::from("Fixed"),
+        };
+        //ignored and fixed findings don't come back
+        if status != ReviewStatus::Accepted {
+            self.load()?;
+        } else if let Some(next) = self.next_unreviewed(self.selected) {
+            self.selected = next;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let current = self.current().map(|f| fingerprint(&f.diagnostic));
+        self.review.progress().current = current;
+        self.review.save()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, detail_area, footer_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(12),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+        self.draw_list(frame, list_area);
+        self.draw_detail(frame, detail_area);
+
+        let keys = "↑/↓ move  n next unreviewed  a accept  i ignore  f fix  q quit";
+        let footer = if self.message.is_empty() { keys.to_string() } else { format!("{}  |  {}", keys, self.message) };
+        frame.render_widget(Paragraph::new(footer).dim(), footer_area);
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let reviewed = self.findings.iter()
+            .filter(|f| self.review.progress().status(&f.diagnostic).is_some())
+            .count();
+        let title = format!("Findings ({} of {} reviewed)", reviewed, self.findings.len());
+
+        let rows = rows(&self.findings);
+        let mut selected_row = None;
+        let items: Vec<ListItem> = rows.iter().enumerate()
+            .map(|(row_index, row)| match row {
+                Row::Chapter(heading) => ListItem::new(heading.clone()).bold(),
+                Row::Finding(i) => {
+                    if *i == self.selected {
+                        selected_row = Some(row_index);
+                    }
+                    let diagnostic = &self.findings[*i].diagnostic;
+                    let mark = match self.review.progress().status(diagnostic) {
+                        Some(ReviewStatus::Accepted) => "✓",
+                        Some(_) => "·",
+                        None => " ",
+                    };
+                    ListItem::new(format!("  {} ¶{:<4} {:<26} {:?}",
+                        mark, diagnostic.location.paragraph_number, diagnostic.action.description(), diagnostic.text))
+                }
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed());
+        let mut state = ListState::default().with_selected(selected_row);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let Some(finding) = self.current() else {
+            let block = Block::bordered().title("Nothing to review");
+            frame.render_widget(Paragraph::new("No findings.").block(block), area);
+            return;
+        };
+        let diagnostic = &finding.diagnostic;
+        let mut lines = vec![highlighted(finding), Line::from("")];
+        lines.push(Line::from(format!("{}: {}", diagnostic.action.name(), diagnostic.action.explanation())).dim());
+        if let Some(suggestion) = &diagnostic.suggestion {
+            lines.push(Line::from(vec![TextSpan::raw("Suggestion: "), TextSpan::raw(suggestion.as_str()).green()]));
+        }
+        let block = Block::bordered().title(diagnostic.location.to_string());
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Result<bool, String> {
+        self.message.clear();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') =>
+                self.selected = (self.selected + 1).min(self.findings.len().saturating_sub(1)),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::PageDown => self.selected = (self.selected + 10).min(self.findings.len().saturating_sub(1)),
+            KeyCode::Char('n') => match self.next_unreviewed(self.selected + 1) {
+                Some(next) => self.selected = next,
+                None => self.message = String::from("Nothing left to review after this"),
+            },
+            KeyCode::Char('a') => self.decide(ReviewStatus::Accepted)?,
+            KeyCode::Char('i') => self.decide(ReviewStatus::Ignored)?,
+            KeyCode::Char('f') => self.decide(ReviewStatus::Fixed)?,
+            _ => (),
+        }
+        Ok(true)
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                //a failed fix or save is shown, and the review goes on
+                match self.handle_key(key.code) {
+                    Ok(true) => (),
+                    Ok(false) => break,
+                    Err(e) => self.message = format!("error: {}", e),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Review findings in the terminal, a chapter at a time, accepting,
+/// ignoring or fixing each one.
+pub fn run(review: &mut dyn Review) -> Result<(), Box<dyn Error>> {
+    let mut app = App { review, findings: vec![], selected: 0, message: String::new() };
+    app.load()?;
+    app.resume();
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result?;
+    app.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{Action, Checker};
+    use crate::config::Config;
+    use crate::markdown::parse_str;
+
+    const TEXT: &str = "# One\n\nShe was there just then. It is late.\n\n# Two\n\nThey were there just then.\n";
+
+    fn all_findings() -> Vec<Finding> {
+        let config = Config::default();
+        let document = parse_str(TEXT, "test.md", &config);
+        let diagnostics = Checker::with_rules(config, vec![Action::WeakImmediacy, Action::Contraction]).check(&document);
+        findings(&document, diagnostics)
+    }
+
+    //a review that keeps everything in memory, and drops findings that are
+    //ignored or fixed as the real one does
+    #[derive(Default)]
+    struct TestReview {
+        progress: ReviewProgress,
+        acted: Vec<(String, ReviewStatus)>,
+        saves: usize,
+    }
+
+    impl Review for TestReview {
+        fn findings(&mut self) -> Result<Vec<Finding>, String> {
+            let progress = &self.progress;
+            Ok(all_findings().into_iter()
+                .filter(|f| !matches!(progress.status(&f.diagnostic), Some(ReviewStatus::Ignored | ReviewStatus::Fixed)))
+                .collect())
+        }
+
+        fn progress(&mut self) -> &mut ReviewProgress {
+            &mut self.progress
+        }
+
+        fn act(&mut self, diagnostic: &Diagnostic, status: ReviewStatus) -> Result<(), String> {
+            self.acted.push((diagnostic.text.clone(), status));
+            Ok(())
+        }
+
+        fn save(&mut self) -> Result<(), String> {
+            self.saves += 1;
+            Ok(())
+        }
+    }
+
+    fn start(review: &mut TestReview) -> App<'_> {
+        let mut app = App { review, findings: vec![], selected: 0, message: String::new() };
+        app.load().unwrap();
+        app.resume();
+        app
+    }
+
+    fn texts(app: &App) -> Vec<String> {
+        app.findings.iter().map(|f| f.diagnostic.text.clone()).collect()
+    }
+
+    #[test]
+    fn findings_have_their_paragraphs() {
+        let findings = all_findings();
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].paragraph, "She was there just then. It is late.");
+        assert_eq!(findings[2].paragraph, "They were there just then.");
+        let line = highlighted(&findings[1]);
+        let spans: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(spans, vec!["She was there just then. ", "It is", " late."]);
+    }
+
+    #[test]
+    fn rows_are_grouped_by_chapter() {
+        let mut findings = all_findings();
+        findings[2].diagnostic.location.chapter_title = String::from("");
+        let rows: Vec<String> = rows(&findings).into_iter()
+            .map(|row| match row {
+                Row::Chapter(heading) => heading,
+                Row::Finding(i) => findings[i].diagnostic.text.clone(),
+            })
+            .collect();
+        assert_eq!(rows, vec!["One (test.md)", "just then", "It is", "Chapter 2 (test.md)", "just then"]);
+    }
+
+    #[test]
+    fn accepting_moves_on_and_ignoring_removes() {
+        let mut review = TestReview::default();
+        let mut app = start(&mut review);
+        assert!(app.handle_key(KeyCode::Char('a')).unwrap());
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.message, "Accepted");
+        assert!(app.handle_key(KeyCode::Char('i')).unwrap());
+        assert_eq!(texts(&app), vec!["just then", "just then"]);
+        assert!(!app.handle_key(KeyCode::Char('q')).unwrap());
+        assert_eq!(review.acted, vec![
+            (String::from("just then"), ReviewStatus::Accepted),
+            (String::from("It is"), ReviewStatus::Ignored),
+        ]);
+        assert_eq!(review.saves, 2);
+    }
+
+    #[test]
+    fn fixing_needs_a_suggestion() {
+        let mut review = TestReview::default();
+        let mut app = start(&mut review);
+        app.handle_key(KeyCode::Char('f')).unwrap();
+        assert_eq!(app.message, "No fix for this finding");
+        assert_eq!(app.findings.len(), 3);
+        assert!(review.acted.is_empty());
+    }
+
+    #[test]
+    fn resumes_where_it_left_off() {
+        let mut review = TestReview::default();
+        let mut app = start(&mut review);
+        app.handle_key(KeyCode::Down).unwrap();
+        app.handle_key(KeyCode::Down).unwrap();
+        app.finish().unwrap();
+        assert_eq!(start(&mut review).selected, 2);
+
+        //with nowhere saved, the first finding that hasn't been dealt with
+        let mut review = TestReview::default();
+        let first = all_findings().remove(0).diagnostic;
+        review.progress.mark(&first, ReviewStatus::Accepted);
+        assert_eq!(start(&mut review).selected, 1);
+    }
+}
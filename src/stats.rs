@@ -0,0 +1,323 @@
+use crate::checker::Diagnostic;
+use crate::config::ReadingAgeBand;
+use crate::document::{Document, DocumentElement, Sentence, Word};
+use crate::readability::{count_syllables, Readability};
+
+/// The sentence lengths, in words, that the distribution is split at.
+pub const SENTENCE_LENGTH_BUCKETS: [usize; 7] = [1, 6, 11, 16, 21, 31, 41];
+
+/// Counts of what's in part of a document.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Counts {
+    pub paragraphs: usize,
+    pub sentences: usize,
+    /// How many of the sentences are speech.
+    pub speech_sentences: usize,
+    pub words: usize,
+    /// How many of the words are speech.
+    pub speech_words: usize,
+    pub findings: usize,
+    /// The share of the words that are speech rather than narration, from 0
+    /// to 1.
+    pub speech_ratio: f64,
+    pub findings_per_thousand_words: f64,
+    pub syllables: usize,
+    /// Letters and digits.
+    pub letters: usize,
+    /// Words of three syllables or more.
+    pub polysyllables: usize,
+    pub readability: Readability,
+}
+
+impl Counts {
+    fn add_sentence(&mut self, sentence: &Sentence, speech: bool) {
+        let words: Vec<&Word> = words(sentence).collect();
+        self.sentences += 1;
+        self.words += words.len();
+        if speech {
+            self.speech_sentences += 1;
+            self.speech_words += words.len();
+        }
+        for word in words {
+            let syllables = count_syllables(&word.text);
+            self.syllables += syllables;
+            self.letters += word.text.chars().filter(|c| c.is_alphanumeric()).count();
+            if syllables >= 3 {
+                self.polysyllables += 1;
+            }
+        }
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.paragraphs += other.paragraphs;
+        self.sentences += other.sentences;
+        self.speech_sentences += other.speech_sentences;
+        self.words += other.words;
+        self.speech_words += other.speech_words;
+        self.findings += other.findings;
+        self.syllables += other.syllables;
+        self.letters += other.letters;
+        self.polysyllables += other.polysyllables;
+    }
+
+    //work out the ratios, once everything's counted
+    fn finish(&mut self) {
+        self.speech_ratio = ratio(self.speech_words, self.words);
+        self.findings_per_thousand_words = ratio(self.findings * 1000, self.words);
+        self.readability = Readability::of(self);
+    }
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 { 0.0 } else { a as f64 / b as f64 }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionStats {
+    pub number: i32,
+    pub counts: Counts,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChapterStats {
+    pub number: i32,
+    /// The chapter's title, or failing that its name, if it has either.
+    pub title: String,
+    pub counts: Counts,
+    pub sections: Vec<SectionStats>,
+}
+
+/// How many sentences have a length from `from` words up to, but not
+/// including, `to`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthBucket {
+    pub from: usize,
+    /// None for the last bucket, which has no upper limit.
+    pub to: Option<usize>,
+    pub sentences: usize,
+}
+
+/// How long sentences are, in words.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SentenceLengths {
+    pub average: f64,
+    pub median: usize,
+    pub shortest: usize,
+    pub longest: usize,
+    pub distribution: Vec<LengthBucket>,
+}
+
+impl SentenceLengths {
+    fn of(mut lengths: Vec<usize>) -> SentenceLengths {
+        let distribution = SENTENCE_LENGTH_BUCKETS.iter().enumerate()
+            .map(|(i, from)| {
+                let to = SENTENCE_LENGTH_BUCKETS.get(i + 1).copied();
+                let sentences = lengths.iter().filter(|l| *l >= from && to.is_none_or(|to| **l < to)).count();
+                LengthBucket { from: *from, to, sentences }
+            })
+            .collect();
+        lengths.sort();
+        SentenceLengths {
+            average: ratio(lengths.iter().sum(), lengths.len()),
+            median: lengths.get(lengths.len() / 2).copied().unwrap_or(0),
+            shortest: lengths.first().copied().unwrap_or(0),
+            longest: lengths.last().copied().unwrap_or(0),
+            distribution,
+        }
+    }
+}
+
+/// How evenly the words are spread over the chapters.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChapterBalance {
+    pub mean_words: f64,
+    pub standard_deviation: f64,
+    /// The numbers of the shortest and longest chapters.
+    pub shortest: i32,
+    pub longest: i32,
+}
+
+impl ChapterBalance {
+    fn of(chapters: &[ChapterStats]) -> ChapterBalance {
+        let words: Vec<f64> = chapters.iter().map(|c| c.counts.words as f64).collect();
+        let mean_words = if words.is_empty() { 0.0 } else { words.iter().sum::<f64>() / words.len() as f64 };
+        let variance = if words.is_empty() {
+            0.0
+        } else {
+            words.iter().map(|w| (w - mean_words).powi(2)).sum::<f64>() / words.len() as f64
+        };
+        ChapterBalance {
+            mean_words,
+            standard_deviation: variance.sqrt(),
+            shortest: chapters.iter().min_by_key(|c| c.counts.words).map_or(0, |c| c.number),
+            longest: chapters.iter().max_by_key(|c| c.counts.words).map_or(0, |c| c.number),
+        }
+    }
+}
+
+/// Counts of what's in a document.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    pub chapters: i32,
+    pub sections: usize,
+    pub totals: Counts,
+    pub sentence_lengths: SentenceLengths,
+    pub chapter_balance: ChapterBalance,
+    pub chapter_stats: Vec<ChapterStats>,
+}
+
+//the words of a sentence, without the spaces and punctuation
+fn words(sentence: &Sentence) -> impl Iterator<Item = &Word> {
+    sentence.words.iter().filter(|w| w.text.chars().any(|c| c.is_alphanumeric()))
+}
+
+/// The length in words of every sentence in the document, speech included,
+/// in order.
+pub fn sentence_lengths(document: &Document) -> Vec<usize> {
+    let mut out = vec![];
+    for element in &document.elements {
+        match element {
+            DocumentElement::Sentence(sentence) | DocumentElement::SpeechTag(sentence) => out.push(words(sentence).count()),
+            DocumentElement::DirectSpeech(speech) => out.extend(speech.sentences.iter().map(|s| words(s).count())),
+            _ => (),
+        }
+    }
+    out
+}
+
+fn new_chapter(number: i32) -> ChapterStats {
+    ChapterStats {
+        number,
+        sections: vec![SectionStats { number: 1, counts: Counts::default() }],
+        ..ChapterStats::default()
+    }
+}
+
+impl Stats {
+    /// Count what's in a document, and the findings from checking it.
+    pub fn of(document: &Document, diagnostics: &[Diagnostic]) -> Stats {
+        let mut stats = Stats {
+            chapters: document.number_of_chapters,
+            sections: document.number_of_chapters as usize,
+            ..Stats::default()
+        };
+        let mut chapters = vec![new_chapter(1)];
+        for element in &document.elements {
+            //there's always a chapter with a section in it
+            let chapter = chapters.last_mut().unwrap();
+            let section = chapter.sections.last_mut().unwrap();
+            match element {
+                DocumentElement::ChapterEnd => {
+                    let number = chapter.number + 1;
+                    chapters.push(new_chapter(number));
+                }
+                DocumentElement::SectionEnd => {
+                    stats.sections += 1;
+                    let number = section.number + 1;
+                    chapter.sections.push(SectionStats { number, counts: Counts::default() });
+                }
+                DocumentElement::ChapterTitle(title) => chapter.title = title.clone(),
+                DocumentElement::ChapterName(name) if chapter.title.is_empty() => chapter.title = name.clone(),
+                DocumentElement::ParagraphStart(_) => section.counts.paragraphs += 1,
+                DocumentElement::Sentence(sentence) | DocumentElement::SpeechTag(sentence) => {
+                    section.counts.add_sentence(sentence, false);
+                }
+                DocumentElement::DirectSpeech(speech) => {
+                    for sentence in &speech.sentences {
+                        section.counts.add_sentence(sentence, true);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        //findings know which chapter and section they're in
+        for diagnostic in diagnostics {
+            let location = &diagnostic.location;
+            let section = chapters.iter_mut()
+                .find(|c| c.number == location.chapter_number)
+                .and_then(|c| c.sections.iter_mut().find(|s| s.number == location.section_number));
+            if let Some(section) = section {
+                section.counts.findings += 1;
+            }
+        }
+
+        for chapter in &mut chapters {
+            for section in &mut chapter.sections {
+                section.counts.finish();
+                chapter.counts.add(&section.counts);
+            }
+            chapter.counts.finish();
+            stats.totals.add(&chapter.counts);
+        }
+        stats.totals.finish();
+        stats.sentence_lengths = SentenceLengths::of(sentence_lengths(document));
+        stats.chapter_balance = ChapterBalance::of(&chapters);
+        stats.chapter_stats = chapters;
+        stats
+    }
+
+    /// The chapters whose reading age is outside the band.
+    pub fn chapters_outside(&self, band: &ReadingAgeBand) -> Vec<&ChapterStats> {
+        self.chapter_stats.iter()
+            .filter(|chapter| chapter.counts.words > 0 && !band.contains(chapter.counts.readability.reading_age))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DocumentBuilder;
+    use crate::config::Config;
+    use crate::document::Paragraph;
+
+    fn paragraph(index: usize, text: &str) -> Paragraph {
+        Paragraph {
+            index,
+            text: text.to_string(),
+            file: String::from("test.odt"),
+            source_path: String::from(""),
+            offset: 0,
+            suppressions: vec![],
+        }
+    }
+
+    #[test]
+    fn page_break_before_the_first_paragraph() {
+        let config = Config::default();
+        let mut builder = DocumentBuilder::new(&config);
+        builder.chapter_end();
+        builder.paragraph(paragraph(0, "She ran to the door."));
+        builder.chapter_end();
+        builder.paragraph(paragraph(1, "She fell."));
+        let document = builder.finish();
+
+        let stats = Stats::of(&document, &[]);
+        assert_eq!(stats.chapters, 2);
+        let words: Vec<usize> = stats.chapter_stats.iter().map(|c| c.counts.words).collect();
+        assert_eq!(words, vec![5, 2]);
+        assert_eq!(stats.chapter_balance.shortest, 2);
+        assert_eq!(stats.chapter_balance.mean_words, 3.5);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::checker::Action;
+
+/// How much of the manuscript a suppression covers.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum SuppressionScope {
+    Paragraph,
+    Section,
+    Chapter,
+}
+
+/// An author's note not to report some rules, from an ODT comment like
+/// 'teastain:ignore filtering' or a Markdown '<!-- teastain-ignore -->'.
+#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suppression {
+    /// The rules not to report. Empty means all of them.
+    pub rules: Vec<Action>,
+    pub scope: SuppressionScope,
+}
+
+const MARKERS: [&str; 2] = ["teastain:ignore", "teastain-ignore"];
+
+impl Suppression {
+    /// Read a suppression from a comment: a marker, then any rules and a
+    /// scope, e.g. 'teastain:ignore filtering, echo section'. The scope is
+    /// the paragraph unless it says otherwise. Returns None for comments
+    /// that aren't suppressions, and the word we didn't understand for ones
+    /// that are but are misspelt.
+    pub fn parse(comment: &str) -> Option<Result<Suppression, String>> {
+        let start = MARKERS.iter().find_map(|marker| comment.find(marker).map(|i| i + marker.len()))?;
+        let rest = comment[start..].lines().next().unwrap_or("");
+
+        let mut suppression = Suppression { rules: vec![], scope: SuppressionScope::Paragraph };
+        for word in rest.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+            match word {
+                "paragraph" => suppression.scope = SuppressionScope::Paragraph,
+                "section" => suppression.scope = SuppressionScope::Section,
+                "chapter" => suppression.scope = SuppressionScope::Chapter,
+                //the end of a Markdown comment
+                "-->" => break,
+                _ => match Action::from_name(word) {
+                    Some(action) => suppression.rules.push(action),
+                    None => return Some(Err(word.to_string())),
+                },
+            }
+        }
+        Some(Ok(suppression))
+    }
+
+    pub fn covers(&self, action: Action) -> bool {
+        self.rules.is_empty() || self.rules.contains(&action)
+    }
+}
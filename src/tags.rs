@@ -0,0 +1,71 @@
+use crate::config::Config;
+use crate::document::Word;
+
+/// What a dialogue tag rule found.
+pub enum TagFinding {
+    /// A speech verb other than the plain ones, e.g. 'growled'.
+    Bookism(Word),
+    /// An adverb hanging off the speech verb, e.g. 'said angrily'.
+    Adverb(Word),
+}
+
+//how far into the tag we look for the verb: far enough for 'the old man said'
+const TAG_VERB_WINDOW: usize = 4;
+//how far from the verb an adverb can be and still be attached to it:
+//far enough for 'said Thurl, gently'
+const TAG_ADVERB_WINDOW: usize = 2;
+
+/// Look at the narration attached to a piece of speech. If it has a speech
+/// verb near its start, report the verb if it's a said-bookism and any
+/// adverb attached to it.
+pub fn analyse_speech_tag(tag: &[Word], config: &Config) -> Vec<TagFinding> {
+    let mut out = vec![];
+
+    let words: Vec<&Word> = tag.iter().filter(|w| w.text.chars().any(|c| c.is_alphabetic())).collect();
+    let lowercase_words: Vec<String> = words.iter().map(|w| w.text.to_lowercase()).collect();
+
+    let is_speech_verb = |w: &String| config.speech_verbs.contains(w) || config.said_bookisms.contains(w);
+    let verb_index = match lowercase_words.iter().take(TAG_VERB_WINDOW).position(is_speech_verb) {
+        Some(verb_index) => verb_index,
+        None => return out,
+    };
+
+    if config.said_bookisms.contains(&lowercase_words[verb_index]) {
+        out.push(TagFinding::Bookism(words[verb_index].clone()));
+    }
+
+    //names like Emily and Molly end in -ly too
+    let is_name = |i: usize| {
+        words[i].text.starts_with(char::is_uppercase)
+            || config.character_names.iter().any(|n| n.to_lowercase() == lowercase_words[i])
+    };
+    let start = verb_index.saturating_sub(1);
+    let end = (verb_index + TAG_ADVERB_WINDOW).min(words.len() - 1);
+    for i in start..=end {
+        if i != verb_index && lowercase_words[i].ends_with("ly") && !is_name(i) {
+            out.push(TagFinding::Adverb(words[i].clone()));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checker::{check_markdown, findings, Action};
+    use crate::config::Config;
+
+    #[test]
+    fn adverb_in_tag() {
+        assert_eq!(findings("‘Go,’ she said angrily.\n", Action::TagAdverb), vec!["angrily"]);
+    }
+
+    #[test]
+    fn names_ending_in_ly() {
+        assert!(findings("‘Hello,’ said Emily.\n", Action::TagAdverb).is_empty());
+        assert!(findings("‘Go,’ Molly whispered.\n", Action::TagAdverb).is_empty());
+
+        let config = Config { character_names: vec![String::from("Holly")], ..Config::default() };
+        assert!(check_markdown("‘Go,’ whispered holly.\n", Action::TagAdverb, config).is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+//! The library as another program would use it, through what it exports.
+
+use std::path::Path;
+
+use teastain::{parse, Action, Checker, Config, DocumentElement, Stats, TeastainError};
+
+#[test]
+fn check_a_manuscript() {
+    let config = Config::default();
+    let document = parse(Path::new("res/sd1.odt"), &config).unwrap();
+    assert!(document.elements.iter().any(|e| matches!(e, DocumentElement::Sentence(_))));
+
+    let diagnostics = Checker::new(config).check(&document);
+    assert!(!diagnostics.is_empty());
+    for diagnostic in &diagnostics {
+        assert!(diagnostic.location.chapter_number >= 1);
+        assert!(diagnostic.to_string().contains(diagnostic.action.description()));
+    }
+
+    let stats = Stats::of(&document, &diagnostics);
+    assert_eq!(stats.chapter_stats.len(), document.number_of_chapters as usize);
+}
+
+#[test]
+fn check_some_rules() {
+    let config = Config::default();
+    let document = parse(Path::new("res/sd1.odt"), &config).unwrap();
+    let checker = Checker::with_rules(config, vec![Action::Contraction]);
+    assert_eq!(checker.rules(), &[Action::Contraction]);
+    let diagnostics = checker.check(&document);
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics.iter().all(|d| d.action == Action::Contraction && d.suggestion.is_some()));
+}
+
+#[test]
+fn rules_by_name() {
+    for action in Action::ALL {
+        assert_eq!(Action::from_name(action.name()), Some(action));
+        assert!(!action.explanation().is_empty());
+    }
+    assert_eq!(Action::from_name("no-such-rule"), None);
+}
+
+#[test]
+fn unreadable_files() {
+    let config = Config::default();
+    assert!(matches!(parse(Path::new("novel.docx"), &config), Err(TeastainError::UnsupportedFormat(e)) if e == "docx"));
+    assert!(matches!(parse(Path::new("res/missing.odt"), &config), Err(TeastainError::Io(_))));
+}